@@ -0,0 +1,394 @@
+use std::{
+    env,
+    error::Error,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+use particle_life::{
+    boundary::Boundary,
+    capture::{GifRecorder, PngSequence, GIF_DELAY},
+    color::Color,
+    coloring::{ColorMode, Coloring},
+    integrator::Integrator,
+    kernel::{self, ForceKernel},
+    preset::Preset,
+    raster::Raster,
+    rules::Rules,
+    share::WorldCode,
+    snapshot::Snapshot,
+    svg::{Svg, SvgOptions},
+    trajectory::TrajectoryRecorder,
+    universe::Universe,
+};
+
+const USAGE: &str = "
+Run a particle life simulation without a window.
+
+USAGE:
+    headless [OPTIONS]
+
+OPTIONS:
+    --preset <NAME>      Rules to generate the universe from [default: Balanced]
+    --rules <FILE>       Load the rules and world size from a TOML file instead
+    --code <CODE>        Start from a world code copied from the window, ignoring --preset and --rules
+    --resume <FILE>      Continue from a snapshot, ignoring the options above
+    --boundary <NAME>    Edges of the world: periodic, reflective, absorbing, soft-walls or disc
+                         [default: from rules, or periodic]
+    --kernel <NAME>      Force law between particles [default: from preset or rules]
+    --integrator <NAME>  How to advance each step [default: from rules, or semi-implicit-euler]
+    --dt <DT>            Length of each step [default: from rules, or 1]
+    --types <N>          Number of particle types [default: from preset]
+    --particles <N>      Number of particles [default: from preset]
+    --width <W>          World width [default: 1600]
+    --height <H>         World height [default: 900]
+    --seed <SEED>        Seed for the random rules and particles [default: random]
+    --steps <N>          Number of steps to run [default: 1000]
+    --every <N>          Steps between reports [default: 100]
+    --save-rules <FILE>  Save the rules of the generated universe to a TOML file
+    --print-code         Print a world code for the starting universe, for sharing
+    --dump <DIR>         Also write every particle's state into DIR at each report
+    --checkpoint <FILE>  Save a snapshot to FILE at each report
+    --png <DIR>          Draw the whole world into a numbered PNG in DIR at each report
+    --gif <FILE>         Draw the whole world into a frame of an animated GIF at each report
+    --svg <DIR>          Draw the whole world into an SVG in DIR at each report
+    --svg-boundary       Outline the edges of the world in SVGs
+    --svg-legend         Add a legend of the type colours to SVGs
+    --trajectory <FILE>  Log every particle's state to FILE, as CSV if it ends in .csv
+                         and in a compact binary format otherwise
+    --stride <N>         Steps between trajectory entries [default: 1]
+    --help               Print this message
+";
+
+/// Particle types are stored in a `u8`.
+const MAX_TYPES: usize = u8::MAX as usize + 1;
+/// The widest or tallest world, in pixels, that can be drawn into PNGs or GIFs.
+const MAX_RASTER_SIZE: usize = 8192;
+
+struct Options {
+    preset: &'static Preset,
+    rules: Option<PathBuf>,
+    code: Option<WorldCode>,
+    resume: Option<PathBuf>,
+    boundary: Option<Boundary>,
+    kernel: Option<&'static dyn ForceKernel>,
+    integrator: Option<Integrator>,
+    dt: Option<f32>,
+    types: Option<usize>,
+    particles: Option<usize>,
+    width: f32,
+    height: f32,
+    seed: u64,
+    steps: u64,
+    every: u64,
+    save_rules: Option<PathBuf>,
+    print_code: bool,
+    dump: Option<PathBuf>,
+    checkpoint: Option<PathBuf>,
+    png: Option<PathBuf>,
+    gif: Option<PathBuf>,
+    svg: Option<PathBuf>,
+    svg_options: SvgOptions,
+    trajectory: Option<PathBuf>,
+    stride: u64,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options {
+            preset: &particle_life::preset::BALANCED,
+            rules: None,
+            code: None,
+            resume: None,
+            boundary: None,
+            kernel: None,
+            integrator: None,
+            dt: None,
+            types: None,
+            particles: None,
+            width: 1600.0,
+            height: 900.0,
+            seed: rand::random(),
+            steps: 1000,
+            every: 100,
+            save_rules: None,
+            print_code: false,
+            dump: None,
+            checkpoint: None,
+            png: None,
+            gif: None,
+            svg: None,
+            svg_options: SvgOptions::default(),
+            trajectory: None,
+            stride: 1,
+        };
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("missing value for '{}'", arg));
+            match arg.as_str() {
+                "--preset" => {
+                    let name = value()?;
+                    options.preset =
+                        Preset::by_name(&name).ok_or(format!("unknown preset '{}'", name))?;
+                }
+                "--rules" => options.rules = Some(PathBuf::from(value()?)),
+                "--code" => {
+                    options.code = Some(WorldCode::decode(&value()?).map_err(|e| e.to_string())?)
+                }
+                "--resume" => options.resume = Some(PathBuf::from(value()?)),
+                "--boundary" => {
+                    let name = value()?;
+                    options.boundary = Some(
+                        Boundary::by_name(&name).ok_or(format!("unknown boundary '{}'", name))?,
+                    );
+                }
+                "--kernel" => {
+                    let name = value()?;
+                    options.kernel =
+                        Some(kernel::by_name(&name).ok_or(format!("unknown kernel '{}'", name))?);
+                }
+                "--integrator" => {
+                    let name = value()?;
+                    options.integrator = Some(
+                        Integrator::by_name(&name)
+                            .ok_or(format!("unknown integrator '{}'", name))?,
+                    );
+                }
+                "--dt" => {
+                    let dt: f32 = parse_value(&arg, value()?)?;
+                    if !(dt > 0.0 && dt.is_finite()) {
+                        return Err(format!("'--dt' must be positive, found {}", dt));
+                    }
+                    options.dt = Some(dt);
+                }
+                "--types" => {
+                    let types: usize = parse_value(&arg, value()?)?;
+                    if !(1..=MAX_TYPES).contains(&types) {
+                        return Err(format!(
+                            "'--types' must be between 1 and {}, found {}",
+                            MAX_TYPES, types
+                        ));
+                    }
+                    options.types = Some(types);
+                }
+                "--particles" => options.particles = Some(parse_value(&arg, value()?)?),
+                "--width" => options.width = parse_size(&arg, value()?)?,
+                "--height" => options.height = parse_size(&arg, value()?)?,
+                "--seed" => options.seed = parse_value(&arg, value()?)?,
+                "--steps" => options.steps = parse_value(&arg, value()?)?,
+                "--every" => options.every = parse_value::<u64>(&arg, value()?)?.max(1),
+                "--save-rules" => options.save_rules = Some(PathBuf::from(value()?)),
+                "--print-code" => options.print_code = true,
+                "--dump" => options.dump = Some(PathBuf::from(value()?)),
+                "--checkpoint" => options.checkpoint = Some(PathBuf::from(value()?)),
+                "--png" => options.png = Some(PathBuf::from(value()?)),
+                "--gif" => options.gif = Some(PathBuf::from(value()?)),
+                "--svg" => options.svg = Some(PathBuf::from(value()?)),
+                "--svg-boundary" => options.svg_options.boundary = true,
+                "--svg-legend" => options.svg_options.legend = true,
+                "--trajectory" => options.trajectory = Some(PathBuf::from(value()?)),
+                "--stride" => options.stride = parse_value::<u64>(&arg, value()?)?.max(1),
+                "--help" | "-h" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                _ => return Err(format!("unknown argument '{}'", arg)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(arg: &str, value: String) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{}' for '{}'", value, arg))
+}
+
+/// Parse a world size, which must be positive.
+fn parse_size(arg: &str, value: String) -> Result<f32, String> {
+    let size: f32 = parse_value(arg, value)?;
+    if size > 0.0 && size.is_finite() {
+        Ok(size)
+    } else {
+        Err(format!(
+            "'{}' must be a positive number, found {}",
+            arg, size
+        ))
+    }
+}
+
+fn dump(universe: &Universe, dir: &Path, step: u64) -> std::io::Result<()> {
+    let mut out = BufWriter::new(File::create(dir.join(format!("step_{:08}.txt", step)))?);
+    writeln!(out, "# index type x y vx vy")?;
+    for (i, p) in universe.particles().iter().enumerate() {
+        writeln!(out, "{} {} {} {} {} {}", i, p.p_type, p.x, p.y, p.vx, p.vy)?;
+    }
+    out.flush()
+}
+
+fn report(universe: &Universe, step: u64) {
+    let len = universe.particles().len().max(1) as f32;
+    let mean_speed = universe
+        .particles()
+        .iter()
+        .map(|p| (p.vx * p.vx + p.vy * p.vy).sqrt())
+        .sum::<f32>()
+        / len;
+    println!(
+        "{:>10} {:>16.6} {:>12.6}",
+        step,
+        universe.kinetic_energy(),
+        mean_speed
+    );
+}
+
+fn build_universe(options: &Options) -> Result<Universe, Box<dyn Error>> {
+    if let Some(path) = &options.resume {
+        return Ok(Universe::from_snapshot(&Snapshot::load(path)?)?);
+    }
+    if let Some(world) = &options.code {
+        return Ok(Universe::from_world_code(world)?);
+    }
+    let preset = options.preset;
+    let num_particles = options.particles.unwrap_or(preset.num_particles);
+    if let Some(path) = &options.rules {
+        let rules = Rules::load(path)?;
+        let mut universe = Universe::new(
+            rules.colors.len(),
+            num_particles,
+            rules.width,
+            rules.height,
+            options.seed,
+        );
+        universe.set_rules(&rules)?;
+        universe.set_random_particles();
+        return Ok(universe);
+    }
+    let mut universe = Universe::new(
+        options.types.unwrap_or(preset.num_types),
+        num_particles,
+        options.width,
+        options.height,
+        options.seed,
+    );
+    universe.re_seed(preset, options.seed);
+    Ok(universe)
+}
+
+fn run(options: Options) -> Result<(), Box<dyn Error>> {
+    let mut universe = build_universe(&options)?;
+    if let Some(boundary) = options.boundary {
+        universe.set_boundary(boundary);
+    }
+    if let Some(kernel) = options.kernel {
+        universe.set_kernel(kernel);
+    }
+    if let Some(integrator) = options.integrator {
+        universe.set_integrator(integrator);
+    }
+    if let Some(dt) = options.dt {
+        universe.set_dt(dt);
+    }
+    if let Some(path) = &options.save_rules {
+        universe.rules().save(path)?;
+    }
+    if let Some(dir) = &options.dump {
+        fs::create_dir_all(dir)?;
+    }
+    let mut raster = if options.png.is_some() || options.gif.is_some() {
+        let (width, height) = (universe.width() as usize, universe.height() as usize);
+        if width > MAX_RASTER_SIZE || height > MAX_RASTER_SIZE {
+            return Err(format!(
+                "the world is too large to draw into images, at most {} pixels a side",
+                MAX_RASTER_SIZE
+            )
+            .into());
+        }
+        Some(Raster::new(width, height))
+    } else {
+        None
+    };
+    let mut pngs = options.png.as_ref().map(PngSequence::new).transpose()?;
+    let mut gif = match (&options.gif, &raster) {
+        (Some(path), Some(raster)) => Some(GifRecorder::create(
+            path,
+            raster.width(),
+            raster.height(),
+            GIF_DELAY,
+        )?),
+        _ => None,
+    };
+    if let Some(dir) = &options.svg {
+        fs::create_dir_all(dir)?;
+    }
+    let mut trajectory = match &options.trajectory {
+        Some(path) => Some(TrajectoryRecorder::create(path, options.stride)?),
+        None => None,
+    };
+    if pngs.is_some() || gif.is_some() || options.svg.is_some() {
+        universe.zoom(universe.width() / 2.0, universe.height() / 2.0, 1.0);
+    }
+
+    println!("# seed {}", universe.seed());
+    if options.print_code {
+        println!("# code {}", WorldCode::new(&universe));
+    }
+    println!(
+        "{:>10} {:>16} {:>12}",
+        "step", "kinetic_energy", "mean_speed"
+    );
+    let end = universe.step_count() + options.steps;
+    loop {
+        let step = universe.step_count();
+        if let Some(trajectory) = &mut trajectory {
+            trajectory.record(&universe)?;
+        }
+        if step % options.every == 0 || step == end {
+            report(&universe, step);
+            if let Some(dir) = &options.dump {
+                dump(&universe, dir, step)?;
+            }
+            if let Some(path) = &options.checkpoint {
+                universe.snapshot(None).save(path)?;
+            }
+            let coloring = Coloring::new(&universe, ColorMode::Type);
+            if let Some(raster) = &mut raster {
+                raster.clear(Color::BLACK);
+                universe.draw(raster, &coloring, 1.0);
+                if let Some(pngs) = &mut pngs {
+                    pngs.write(raster.width(), raster.height(), raster.pixels())?;
+                }
+                if let Some(gif) = &mut gif {
+                    gif.write(raster.pixels())?;
+                }
+            }
+            if let Some(dir) = &options.svg {
+                Svg::render(&universe, &coloring, &options.svg_options)
+                    .save(dir.join(format!("step_{:08}.svg", step)))?;
+            }
+        }
+        if step >= end {
+            if let Some(trajectory) = &mut trajectory {
+                trajectory.flush()?;
+            }
+            return Ok(());
+        }
+        universe.step();
+    }
+}
+
+fn main() {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("error: {}\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(e) = run(options) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
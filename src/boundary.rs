@@ -0,0 +1,148 @@
+use crate::{math::Vector2, particle::Particle};
+
+const WALL_RANGE: f32 = 40.0;
+const WALL_STRENGTH: f32 = 1.0;
+
+/// What happens to particles at the edge of the world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Boundary {
+    /// The world wraps around at its edges like a torus.
+    #[default]
+    Periodic,
+    /// Particles bounce off the edges of the world.
+    Reflective,
+    /// Particles that leave the world are removed from it.
+    Absorbing,
+    /// The edges push particles away with a force that grows linearly over the
+    /// last `WALL_RANGE` units before them.
+    SoftWalls,
+    /// Particles bounce off the edge of the largest disc that fits in the world.
+    Disc,
+}
+
+pub const BOUNDARIES: [Boundary; 5] = [
+    Boundary::Periodic,
+    Boundary::Reflective,
+    Boundary::Absorbing,
+    Boundary::SoftWalls,
+    Boundary::Disc,
+];
+
+impl Boundary {
+    /// A short lowercase name used to select the boundary and to save it in rules.
+    pub fn name(self) -> &'static str {
+        match self {
+            Boundary::Periodic => "periodic",
+            Boundary::Reflective => "reflective",
+            Boundary::Absorbing => "absorbing",
+            Boundary::SoftWalls => "soft-walls",
+            Boundary::Disc => "disc",
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        BOUNDARIES
+            .iter()
+            .copied()
+            .find(|b| b.name().eq_ignore_ascii_case(name))
+    }
+
+    /// The boundary after this one, wrapping around to the first.
+    pub fn next(self) -> Self {
+        let i = BOUNDARIES.iter().position(|&b| b == self).unwrap();
+        BOUNDARIES[(i + 1) % BOUNDARIES.len()]
+    }
+
+    /// Whether opposite edges of the world are joined together.
+    pub fn wraps(self) -> bool {
+        self == Boundary::Periodic
+    }
+
+    /// The force the edges of a world of `size` exert on a particle at
+    /// `(x, y)`, or `None` if they exert none.
+    pub fn wall_force(self, x: f32, y: f32, size: Vector2) -> Option<(f32, f32)> {
+        if self != Boundary::SoftWalls {
+            return None;
+        }
+        let push = |d: f32| WALL_STRENGTH * (1.0 - d / WALL_RANGE).max(0.0);
+        Some((push(x) - push(size.x - x), push(y) - push(size.y - y)))
+    }
+
+    /// Bring a particle that has moved out of a world of `size` back into it,
+    /// keeping it at least `margin` from any wall. Returns `false` if the
+    /// particle should be removed instead.
+    pub fn confine(self, p: &mut Particle, margin: f32, size: Vector2) -> bool {
+        match self {
+            // A fast particle can move more than a whole world in one step.
+            Boundary::Periodic => {
+                p.x = p.x.rem_euclid(size.x);
+                p.y = p.y.rem_euclid(size.y);
+            }
+            Boundary::Reflective => reflect(p, margin, size),
+            Boundary::Absorbing => {
+                return p.x >= 0.0 && p.x < size.x && p.y >= 0.0 && p.y < size.y;
+            }
+            // The walls should have turned the particle around already, but a
+            // fast enough particle can still reach them.
+            Boundary::SoftWalls => reflect(p, 0.0, size),
+            Boundary::Disc => {
+                let (cx, cy) = (0.5 * size.x, 0.5 * size.y);
+                let limit = (0.5 * size.x.min(size.y) - margin).max(0.0);
+                let (dx, dy) = (p.x - cx, p.y - cy);
+                let r = (dx * dx + dy * dy).sqrt();
+                if r > limit && r > 0.0 {
+                    let (nx, ny) = (dx / r, dy / r);
+                    p.x = cx + nx * limit;
+                    p.y = cy + ny * limit;
+                    let outward = p.vx * nx + p.vy * ny;
+                    if outward > 0.0 {
+                        p.vx -= 2.0 * outward * nx;
+                        p.vy -= 2.0 * outward * ny;
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+fn reflect(p: &mut Particle, margin: f32, size: Vector2) {
+    if p.x <= margin {
+        p.vx = -p.vx;
+        p.x = margin;
+    } else if p.x >= size.x - margin {
+        p.vx = -p.vx;
+        p.x = size.x - margin;
+    }
+    if p.y <= margin {
+        p.vy = -p.vy;
+        p.y = margin;
+    } else if p.y >= size.y - margin {
+        p.vy = -p.vy;
+        p.y = size.y - margin;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn periodic_wraps_particles_from_far_outside() {
+        let size = Vector2::new(400.0, 300.0);
+        for &(x, y, wx, wy) in &[
+            (410.0, -10.0, 10.0, 290.0),
+            (1250.0, 700.0, 50.0, 100.0),
+            (-1250.0, -700.0, 350.0, 200.0),
+            (400.0, 0.0, 0.0, 0.0),
+        ] {
+            let mut p = Particle {
+                x,
+                y,
+                ..Default::default()
+            };
+            assert!(Boundary::Periodic.confine(&mut p, 0.0, size));
+            assert_eq!((p.x, p.y), (wx, wy), "from ({}, {})", x, y);
+        }
+    }
+}
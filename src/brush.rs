@@ -0,0 +1,114 @@
+use raylib::{
+    color::Color,
+    prelude::{MouseButton, RaylibDraw, RaylibHandle},
+};
+
+use crate::{camera::Camera, universe::Universe};
+
+const PAINT_PER_FRAME: usize = 2;
+const MIN_RADIUS: f32 = 5.0;
+const MAX_RADIUS: f32 = 200.0;
+const FONT_SIZE: i32 = 10;
+
+/// A mouse tool for drawing particles of one type into the universe by
+/// dragging with the left button and erasing them with the right.
+///
+/// The brush radius is in screen pixels, so it covers less of the world the
+/// further the camera is zoomed in.
+pub struct Brush {
+    active: bool,
+    p_type: usize,
+    radius: f32,
+}
+
+impl Brush {
+    pub fn new() -> Self {
+        Brush {
+            active: false,
+            p_type: 0,
+            radius: 30.0,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.active = !self.active;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Choose the type of particle to paint.
+    pub fn set_type(&mut self, p_type: usize) {
+        self.p_type = p_type;
+    }
+
+    /// Move on to the next of `num_types` types, wrapping around to the first.
+    pub fn next_type(&mut self, num_types: usize) {
+        self.p_type = (self.p_type(num_types) + 1) % num_types.max(1);
+    }
+
+    /// The type to paint, or the last type if the chosen one no longer exists.
+    fn p_type(&self, num_types: usize) -> usize {
+        self.p_type.min(num_types.saturating_sub(1))
+    }
+
+    /// Paint or erase under the mouse and resize the brush with the scroll
+    /// wheel. Returns whether the brush is active, in which case nothing else
+    /// should use the mouse.
+    pub fn update(&mut self, rl: &RaylibHandle, universe: &mut Universe, cam: &mut Camera) -> bool {
+        if !self.active {
+            return false;
+        }
+        let wheel = rl.get_mouse_wheel_move();
+        if wheel != 0.0 {
+            self.radius = (self.radius * 1.1_f32.powf(wheel)).clamp(MIN_RADIUS, MAX_RADIUS);
+        }
+        let mouse = rl.get_mouse_position();
+        if rl.is_mouse_button_down(MouseButton::MOUSE_LEFT_BUTTON) {
+            let p_type = self.p_type(universe.types().len());
+            for _ in 0..PAINT_PER_FRAME {
+                // Uniformly distributed over the disc under the brush.
+                let angle = rand::random::<f32>() * std::f32::consts::TAU;
+                let r = self.radius * rand::random::<f32>().sqrt();
+                let c = universe.get_centre(
+                    (mouse.x + r * angle.cos()).max(0.0) as usize,
+                    (mouse.y + r * angle.sin()).max(0.0) as usize,
+                );
+                universe.add_particle(c.x, c.y, p_type);
+            }
+        } else if rl.is_mouse_button_down(MouseButton::MOUSE_RIGHT_BUTTON) {
+            let c = universe.get_centre(mouse.x as usize, mouse.y as usize);
+            if universe.remove_particles_within(c.x, c.y, self.radius / cam.zoom()) > 0 {
+                // The followed particle may have moved to another index.
+                *cam.track_index_mut() = None;
+            }
+        }
+        true
+    }
+
+    pub fn draw(&self, d: &mut impl RaylibDraw, universe: &Universe, mouse: (i32, i32)) {
+        if !self.active {
+            return;
+        }
+        let types = universe.types();
+        let p_type = self.p_type(types.len());
+        let color = types
+            .color(p_type)
+            .map_or(Color::WHITE, |&c| Color::from(c));
+        d.draw_circle_lines(mouse.0, mouse.1, self.radius, color);
+        d.draw_text(
+            &format!("type {}", p_type + 1),
+            mouse.0 + self.radius as i32 + 4,
+            mouse.1 - FONT_SIZE / 2,
+            FONT_SIZE,
+            color,
+        );
+    }
+}
+
+impl Default for Brush {
+    fn default() -> Self {
+        Self::new()
+    }
+}
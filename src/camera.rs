@@ -1,7 +1,10 @@
 use std::time::Instant;
 
-use crate::{universe::Universe, HEIGHT, WIDTH};
+use serde::{Deserialize, Serialize};
 
+use crate::universe::Universe;
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Camera {
     x: f32,
     y: f32,
@@ -9,14 +12,15 @@ pub struct Camera {
     x_dest: f32,
     y_dest: f32,
     zoom_dest: f32,
+    #[serde(skip, default = "Instant::now")]
     last_scroll_time: Instant,
     track_index: Option<usize>,
 }
 
 impl Camera {
-    pub fn new() -> Self {
-        let x = WIDTH as f32 / 2.0;
-        let y = HEIGHT as f32 / 2.0;
+    pub fn new(width: f32, height: f32) -> Self {
+        let x = width / 2.0;
+        let y = height / 2.0;
         let zoom = 1.0;
         Camera {
             x,
@@ -32,15 +36,62 @@ impl Camera {
 
     pub fn apply_zoom(&mut self, universe: &mut Universe) {
         if let Some(track_index) = self.track_index {
-            self.x_dest = universe.get_particle_x(track_index).unwrap();
-            self.y_dest = universe.get_particle_y(track_index).unwrap();
+            match (
+                universe.get_particle_x(track_index),
+                universe.get_particle_y(track_index),
+            ) {
+                (Some(x), Some(y)) => {
+                    let (x_dest, y_dest) = universe.nearest_image(x, y, self.x, self.y);
+                    self.x_dest = x_dest;
+                    self.y_dest = y_dest;
+                }
+                // The particle no longer exists.
+                _ => self.track_index = None,
+            }
         }
         self.x = self.x * 0.9 + self.x_dest * 0.1;
         self.y = self.y * 0.9 + self.y_dest * 0.1;
+        let (x, y) = universe.wrap_point(self.x, self.y);
+        self.x_dest += x - self.x;
+        self.y_dest += y - self.y;
+        self.x = x;
+        self.y = y;
         self.zoom = self.zoom * 0.8 + self.zoom_dest * 0.2;
+        // Don't let panning push the camera past where the view stops, or
+        // moving back would do nothing until the overshoot was undone.
+        let (x_dest, y_dest) = universe.clamp_view(self.x_dest, self.y_dest, self.zoom_dest);
+        self.x_dest = x_dest;
+        self.y_dest = y_dest;
+        let (x, y) = universe.clamp_view(self.x, self.y, self.zoom);
+        self.x = x;
+        self.y = y;
         universe.zoom(self.x, self.y, self.zoom);
     }
 
+    /// Move towards a point `(dx, dy)` further across the world, and stop
+    /// following any particle.
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        self.x_dest += dx;
+        self.y_dest += dy;
+        self.track_index = None;
+    }
+
+    /// Move by `(dx, dy)` straight away rather than smoothly, so the world
+    /// stays under the mouse while it is dragged.
+    pub fn drag(&mut self, dx: f32, dy: f32) {
+        self.x += dx;
+        self.y += dy;
+        self.pan(dx, dy);
+    }
+
+    /// Zoom back out to show the whole of a world of `width` and `height`.
+    pub fn reset(&mut self, width: f32, height: f32) {
+        self.x_dest = width / 2.0;
+        self.y_dest = height / 2.0;
+        self.zoom_dest = 1.0;
+        self.track_index = None;
+    }
+
     /// Get a mutable reference to the camera's zoom dest.
     pub fn zoom_dest_mut(&mut self) -> &mut f32 {
         &mut self.zoom_dest
@@ -50,6 +101,10 @@ impl Camera {
         self.zoom_dest
     }
 
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
     pub fn last_scroll_time(&self) -> Instant {
         self.last_scroll_time
     }
@@ -63,6 +118,11 @@ impl Camera {
     pub fn y_dest_mut(&mut self) -> &mut f32 {
         &mut self.y_dest
     }
+
+    pub fn track_index(&self) -> Option<usize> {
+        self.track_index
+    }
+
     /// Get a mutable reference to the camera's track index.
     pub fn track_index_mut(&mut self) -> &mut Option<usize> {
         &mut self.track_index
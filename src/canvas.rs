@@ -0,0 +1,7 @@
+use crate::color::Color;
+
+/// Something the universe can be drawn onto, in screen coordinates.
+pub trait Canvas {
+    fn draw_circle(&mut self, x: f32, y: f32, radius: f32, color: Color);
+    fn draw_circle_lines(&mut self, x: f32, y: f32, radius: f32, color: Color);
+}
@@ -0,0 +1,169 @@
+use std::{
+    fmt::Display,
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+};
+
+/// How much effort goes into choosing each GIF frame's palette, from 1 (best)
+/// to 30 (fastest).
+const GIF_SPEED: i32 = 10;
+
+/// How long each frame of a recorded GIF is shown, in hundredths of a second.
+pub const GIF_DELAY: u16 = 3;
+
+/// Save one frame of RGBA pixels, in rows from the top left, as a PNG.
+pub fn save_png(
+    path: impl AsRef<Path>,
+    width: usize,
+    height: usize,
+    pixels: &[u8],
+) -> Result<(), CaptureError> {
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(File::create(path)?),
+        width as u32,
+        height as u32,
+    );
+    encoder.set_color(png::ColorType::RGBA);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(pixels)?;
+    Ok(())
+}
+
+/// Writes frames into a directory as `frame_000000.png`, `frame_000001.png`
+/// and so on.
+pub struct PngSequence {
+    dir: PathBuf,
+    next: u32,
+}
+
+impl PngSequence {
+    /// Start a sequence in `dir`, creating it if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, CaptureError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(PngSequence { dir, next: 0 })
+    }
+
+    pub fn write(
+        &mut self,
+        width: usize,
+        height: usize,
+        pixels: &[u8],
+    ) -> Result<(), CaptureError> {
+        let path = self.dir.join(format!("frame_{:06}.png", self.next));
+        save_png(path, width, height, pixels)?;
+        self.next += 1;
+        Ok(())
+    }
+
+    /// The number of frames written so far.
+    pub fn len(&self) -> u32 {
+        self.next
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.next == 0
+    }
+}
+
+/// Writes frames into a looping animated GIF, each with its own palette.
+/// The file is complete once the recorder is dropped.
+pub struct GifRecorder {
+    encoder: gif::Encoder<BufWriter<File>>,
+    width: u16,
+    height: u16,
+    delay: u16,
+    frames: u32,
+}
+
+impl GifRecorder {
+    /// Start a GIF of frames `width` by `height` shown for `delay` hundredths
+    /// of a second each.
+    pub fn create(
+        path: impl AsRef<Path>,
+        width: usize,
+        height: usize,
+        delay: u16,
+    ) -> Result<Self, CaptureError> {
+        let (width, height) = (gif_size(width)?, gif_size(height)?);
+        let mut encoder =
+            gif::Encoder::new(BufWriter::new(File::create(path)?), width, height, &[])?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+        Ok(GifRecorder {
+            encoder,
+            width,
+            height,
+            delay,
+            frames: 0,
+        })
+    }
+
+    /// Add a frame of RGBA pixels, in rows from the top left, the same size
+    /// as the GIF.
+    pub fn write(&mut self, pixels: &[u8]) -> Result<(), CaptureError> {
+        let mut pixels = pixels.to_vec();
+        let mut frame =
+            gif::Frame::from_rgba_speed(self.width, self.height, &mut pixels, GIF_SPEED);
+        frame.delay = self.delay;
+        self.encoder.write_frame(&frame)?;
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// The number of frames written so far.
+    pub fn len(&self) -> u32 {
+        self.frames
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames == 0
+    }
+}
+
+fn gif_size(size: usize) -> Result<u16, CaptureError> {
+    if size > 0 && size <= u16::MAX as usize {
+        Ok(size as u16)
+    } else {
+        Err(CaptureError::Size(size))
+    }
+}
+
+#[derive(Debug)]
+pub enum CaptureError {
+    Io(io::Error),
+    Png(png::EncodingError),
+    Gif(gif::EncodingError),
+    Size(usize),
+}
+
+impl Display for CaptureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CaptureError::Io(e) => write!(f, "{}", e),
+            CaptureError::Png(e) => write!(f, "could not encode PNG: {}", e),
+            CaptureError::Gif(e) => write!(f, "could not encode GIF: {}", e),
+            CaptureError::Size(size) => write!(f, "{} pixels is too large for a GIF", size),
+        }
+    }
+}
+
+impl std::error::Error for CaptureError {}
+
+impl From<io::Error> for CaptureError {
+    fn from(e: io::Error) -> Self {
+        CaptureError::Io(e)
+    }
+}
+
+impl From<png::EncodingError> for CaptureError {
+    fn from(e: png::EncodingError) -> Self {
+        CaptureError::Png(e)
+    }
+}
+
+impl From<gif::EncodingError> for CaptureError {
+    fn from(e: gif::EncodingError) -> Self {
+        CaptureError::Gif(e)
+    }
+}
@@ -0,0 +1,59 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// An RGBA colour, independent of any renderer.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::new(0, 0, 0, 255);
+    pub const WHITE: Color = Color::new(255, 255, 255, 255);
+
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Color { r, g, b, a }
+    }
+
+    /// The same colour with its alpha set to `alpha`, from 0.0 to 1.0.
+    pub fn fade(&self, alpha: f32) -> Color {
+        Color {
+            a: (255.0 * alpha.clamp(0.0, 1.0)) as u8,
+            ..*self
+        }
+    }
+}
+
+/// Colours are written as `"#rrggbbaa"` hex strings.
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!(
+            "#{:02x}{:02x}{:02x}{:02x}",
+            self.r, self.g, self.b, self.a
+        ))
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let hex = s.strip_prefix('#').unwrap_or(&s);
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|c| u8::from_str_radix(c, 16).ok())
+                .ok_or_else(|| de::Error::custom(format!("invalid colour '{}'", s)))
+        };
+        match hex.len() {
+            6 => Ok(Color::new(channel(0)?, channel(2)?, channel(4)?, 255)),
+            8 => Ok(Color::new(
+                channel(0)?,
+                channel(2)?,
+                channel(4)?,
+                channel(6)?,
+            )),
+            _ => Err(de::Error::custom(format!("invalid colour '{}'", s))),
+        }
+    }
+}
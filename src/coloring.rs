@@ -0,0 +1,211 @@
+use crate::{color::Color, universe::Universe};
+
+const DENSITY_RADIUS: f32 = 25.0;
+const CLUSTER_LINK: f32 = 1.5;
+
+/// The colours a scale runs through, from its lowest value to its highest.
+const SCALE: [Color; 5] = [
+    Color::new(68, 1, 84, 255),
+    Color::new(59, 82, 139, 255),
+    Color::new(33, 145, 140, 255),
+    Color::new(94, 201, 98, 255),
+    Color::new(253, 231, 37, 255),
+];
+const UNCLUSTERED: Color = Color::new(80, 80, 80, 255);
+
+/// What decides the colour each particle is drawn in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMode {
+    /// The colour of the particle's type.
+    #[default]
+    Type,
+    /// How fast the particle is moving.
+    Speed,
+    /// How many particles are within `DENSITY_RADIUS` of it.
+    Density,
+    /// How many particles are within the `max_r` of it, and so push or pull it.
+    Neighbours,
+    /// Which cluster it belongs to, where particles closer than one and a half
+    /// times the `min_r` between them are in the same cluster.
+    Cluster,
+}
+
+pub const COLOR_MODES: [ColorMode; 5] = [
+    ColorMode::Type,
+    ColorMode::Speed,
+    ColorMode::Density,
+    ColorMode::Neighbours,
+    ColorMode::Cluster,
+];
+
+impl ColorMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorMode::Type => "type",
+            ColorMode::Speed => "speed",
+            ColorMode::Density => "density",
+            ColorMode::Neighbours => "neighbours",
+            ColorMode::Cluster => "cluster",
+        }
+    }
+
+    /// The mode after this one, wrapping around to the first.
+    pub fn next(self) -> Self {
+        let i = COLOR_MODES.iter().position(|&m| m == self).unwrap();
+        COLOR_MODES[(i + 1) % COLOR_MODES.len()]
+    }
+}
+
+/// What the colours of a `Coloring` mean.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Legend {
+    /// Each type has its own colour.
+    Types,
+    /// The colours run along `SCALE` from `min` to `max` of the named quantity.
+    Scale {
+        label: &'static str,
+        min: f32,
+        max: f32,
+    },
+    /// Each cluster of more than one particle has its own colour.
+    Clusters(usize),
+}
+
+/// The colour of every particle, in the same order as the particles.
+pub struct Coloring {
+    pub colors: Vec<Color>,
+    pub legend: Legend,
+}
+
+impl Coloring {
+    pub fn new(universe: &Universe, mode: ColorMode) -> Self {
+        let types = universe.types();
+        let particles = universe.particles();
+        match mode {
+            ColorMode::Type => Coloring {
+                colors: particles
+                    .iter()
+                    .map(|p| *types.color(p.p_type as usize).unwrap())
+                    .collect(),
+                legend: Legend::Types,
+            },
+            ColorMode::Speed => {
+                let speeds = particles
+                    .iter()
+                    .map(|p| (p.vx * p.vx + p.vy * p.vy).sqrt())
+                    .collect();
+                Self::scaled(speeds, "speed")
+            }
+            ColorMode::Density => {
+                let mut counts = vec![0.0; particles.len()];
+                universe.for_each_pair(DENSITY_RADIUS, |i, _, _| counts[i] += 1.0);
+                Self::scaled(counts, "particles within 25")
+            }
+            ColorMode::Neighbours => {
+                let mut counts = vec![0.0; particles.len()];
+                universe.for_each_pair(types.largest_max_r(), |i, j, r| {
+                    let (p, q) = (particles[i].p_type as usize, particles[j].p_type as usize);
+                    if r < *types.max_r(p, q).unwrap() {
+                        counts[i] += 1.0;
+                    }
+                });
+                Self::scaled(counts, "neighbours within max_r")
+            }
+            ColorMode::Cluster => {
+                let mut clusters = Clusters::new(particles.len());
+                universe.for_each_pair(types.largest_max_r(), |i, j, r| {
+                    let (p, q) = (particles[i].p_type as usize, particles[j].p_type as usize);
+                    let min_r = types.min_r(p, q).unwrap().max(types.contact_distance(p, q));
+                    if r < (CLUSTER_LINK * min_r).min(*types.max_r(p, q).unwrap()) {
+                        clusters.join(i, j);
+                    }
+                });
+                clusters.coloring()
+            }
+        }
+    }
+
+    fn scaled(values: Vec<f32>, label: &'static str) -> Self {
+        let max = values.iter().fold(0.0_f32, |acc, &v| acc.max(v));
+        Coloring {
+            colors: values
+                .iter()
+                .map(|&v| scale_color(if max > 0.0 { v / max } else { 0.0 }))
+                .collect(),
+            legend: Legend::Scale {
+                label,
+                min: 0.0,
+                max,
+            },
+        }
+    }
+}
+
+/// The colour `t` of the way along `SCALE`, where `t` is from 0.0 to 1.0.
+pub fn scale_color(t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0) * (SCALE.len() - 1) as f32;
+    let i = (t as usize).min(SCALE.len() - 2);
+    let f = t - i as f32;
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * f) as u8;
+    let (a, b) = (SCALE[i], SCALE[i + 1]);
+    Color::new(mix(a.r, b.r), mix(a.g, b.g), mix(a.b, b.b), 255)
+}
+
+/// Disjoint sets of particles, each named by the lowest index in it so that
+/// clusters keep their colours from frame to frame.
+struct Clusters {
+    parent: Vec<usize>,
+}
+
+impl Clusters {
+    fn new(len: usize) -> Self {
+        Clusters {
+            parent: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    fn join(&mut self, i: usize, j: usize) {
+        let (a, b) = (self.find(i), self.find(j));
+        self.parent[a.max(b)] = a.min(b);
+    }
+
+    fn coloring(mut self) -> Coloring {
+        let len = self.parent.len();
+        let roots: Vec<usize> = (0..len).map(|i| self.find(i)).collect();
+        let mut sizes = vec![0; len];
+        for &root in roots.iter() {
+            sizes[root] += 1;
+        }
+        let colors = roots
+            .iter()
+            .map(|&root| {
+                if sizes[root] > 1 {
+                    hue_color((root as f32 * 0.618_034).fract())
+                } else {
+                    UNCLUSTERED
+                }
+            })
+            .collect();
+        Coloring {
+            colors,
+            legend: Legend::Clusters(sizes.iter().filter(|&&s| s > 1).count()),
+        }
+    }
+}
+
+/// A fully saturated colour with hue `h`, from 0.0 to 1.0.
+fn hue_color(h: f32) -> Color {
+    let channel = |offset: f32| {
+        let x = ((h + offset).fract() * 6.0 - 3.0).abs() - 1.0;
+        (255.0 * x.clamp(0.0, 1.0)) as u8
+    };
+    Color::new(channel(0.0), channel(2.0 / 3.0), channel(1.0 / 3.0), 255)
+}
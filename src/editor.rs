@@ -0,0 +1,291 @@
+use raylib::{
+    color::Color,
+    prelude::{MouseButton, RaylibDraw, RaylibHandle},
+};
+
+use crate::{particle::ParticleTypes, universe::Universe};
+
+const LEFT: i32 = 10;
+const TOP: i32 = 40;
+const TAB_WIDTH: i32 = 60;
+const TAB_HEIGHT: i32 = 18;
+const MAX_SIZE: i32 = 400;
+const MAX_CELL: i32 = 32;
+const FONT_SIZE: i32 = 10;
+const MIN_ATTRACT_STEP: f32 = 0.001;
+
+/// One of the interaction matrices the editor can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Matrix {
+    Attract,
+    MinR,
+    MaxR,
+}
+
+const MATRICES: [Matrix; 3] = [Matrix::Attract, Matrix::MinR, Matrix::MaxR];
+
+impl Matrix {
+    fn name(self) -> &'static str {
+        match self {
+            Matrix::Attract => "attract",
+            Matrix::MinR => "min_r",
+            Matrix::MaxR => "max_r",
+        }
+    }
+
+    /// How much one click or notch of the scroll wheel changes a value by.
+    /// Attraction changes by a tenth of the strongest attraction, as presets
+    /// generate values of very different sizes.
+    fn step(self, types: &ParticleTypes) -> f32 {
+        match self {
+            Matrix::Attract => (0.1 * types.largest_attract()).max(MIN_ATTRACT_STEP),
+            Matrix::MinR => 1.0,
+            Matrix::MaxR => 2.0,
+        }
+    }
+
+    fn get(self, types: &ParticleTypes, i: usize, j: usize) -> f32 {
+        match self {
+            Matrix::Attract => *types.attract(i, j).unwrap(),
+            Matrix::MinR => *types.min_r(i, j).unwrap(),
+            Matrix::MaxR => *types.max_r(i, j).unwrap(),
+        }
+    }
+
+    /// Change the value for `i` and `j` by `delta`. The radii are kept
+    /// symmetric and `min_r` is kept between zero and `max_r`.
+    fn change(self, types: &mut ParticleTypes, i: usize, j: usize, delta: f32) {
+        match self {
+            Matrix::Attract => *types.attract_mut(i, j).unwrap() += delta,
+            Matrix::MinR => {
+                let max_r = *types.max_r(i, j).unwrap();
+                let min_r = (*types.min_r(i, j).unwrap() + delta).clamp(0.0, max_r);
+                *types.min_r_mut(i, j).unwrap() = min_r;
+                *types.min_r_mut(j, i).unwrap() = min_r;
+            }
+            Matrix::MaxR => {
+                let min_r = *types.min_r(i, j).unwrap();
+                let max_r = (*types.max_r(i, j).unwrap() + delta).max(min_r);
+                *types.max_r_mut(i, j).unwrap() = max_r;
+                *types.max_r_mut(j, i).unwrap() = max_r;
+            }
+        }
+    }
+}
+
+/// An overlay showing one of the interaction matrices as a grid of cells,
+/// with the colour of each particle type along its edges.
+///
+/// Each row is the type being pushed and each column the type pushing it.
+/// Scrolling over a cell or left clicking it increases the value, and right
+/// clicking decreases it, while the simulation keeps running.
+pub struct MatrixEditor {
+    visible: bool,
+    matrix: Matrix,
+}
+
+impl MatrixEditor {
+    pub fn new() -> Self {
+        MatrixEditor {
+            visible: false,
+            matrix: Matrix::Attract,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Apply any edits made with the mouse. Returns whether the mouse is over
+    /// the editor, in which case nothing else should use it.
+    pub fn update(&mut self, rl: &RaylibHandle, universe: &mut Universe) -> bool {
+        if !self.visible {
+            return false;
+        }
+        let layout = Layout::new(universe.types().len());
+        let mouse = rl.get_mouse_position();
+        let (x, y) = (mouse.x as i32, mouse.y as i32);
+        if !layout.contains(x, y) {
+            return false;
+        }
+        let left = rl.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON);
+        let right = rl.is_mouse_button_pressed(MouseButton::MOUSE_RIGHT_BUTTON);
+        if let Some(matrix) = layout.tab_at(x, y) {
+            if left {
+                self.matrix = matrix;
+            }
+        } else if let Some((i, j)) = layout.cell_at(x, y) {
+            let mut notches = rl.get_mouse_wheel_move();
+            if left {
+                notches += 1.0;
+            }
+            if right {
+                notches -= 1.0;
+            }
+            if notches != 0.0 {
+                let matrix = self.matrix;
+                let step = matrix.step(universe.types());
+                matrix.change(universe.types_mut(), i, j, notches * step);
+            }
+        }
+        true
+    }
+
+    pub fn draw(&self, d: &mut impl RaylibDraw, universe: &Universe, mouse: (i32, i32)) {
+        if !self.visible {
+            return;
+        }
+        let types = universe.types();
+        let layout = Layout::new(types.len());
+        d.draw_rectangle(
+            LEFT - 5,
+            TOP - 5,
+            layout.width() + 10,
+            layout.height() + 10,
+            Color::new(0, 0, 0, 200),
+        );
+
+        for (k, &matrix) in MATRICES.iter().enumerate() {
+            let color = if matrix == self.matrix {
+                Color::WHITE
+            } else {
+                Color::GRAY
+            };
+            d.draw_text(
+                matrix.name(),
+                LEFT + k as i32 * TAB_WIDTH,
+                TOP + 4,
+                FONT_SIZE,
+                color,
+            );
+        }
+
+        let cell = layout.cell;
+        for i in 0..types.len() {
+            let color = Color::from(*types.color(i).unwrap());
+            let offset = i as i32 * cell + 1;
+            d.draw_rectangle(
+                layout.x + offset,
+                layout.y - cell + 1,
+                cell - 2,
+                cell - 2,
+                color,
+            );
+            d.draw_rectangle(
+                layout.x - cell + 1,
+                layout.y + offset,
+                cell - 2,
+                cell - 2,
+                color,
+            );
+        }
+
+        let largest = types.largest_max_r().max(1.0);
+        let strongest = types.largest_attract().max(MIN_ATTRACT_STEP);
+        for i in 0..types.len() {
+            for j in 0..types.len() {
+                let value = self.matrix.get(types, i, j);
+                let color = match self.matrix {
+                    Matrix::Attract if value >= 0.0 => {
+                        Color::new(0, (255.0 * value / strongest) as u8, 0, 255)
+                    }
+                    Matrix::Attract => Color::new((255.0 * -value / strongest) as u8, 0, 0, 255),
+                    _ => {
+                        let v = (255.0 * value / largest) as u8;
+                        Color::new(v, v, v, 255)
+                    }
+                };
+                let (cx, cy) = (layout.x + j as i32 * cell, layout.y + i as i32 * cell);
+                d.draw_rectangle(cx + 1, cy + 1, cell - 2, cell - 2, color);
+                if cell >= 24 {
+                    let text = match self.matrix {
+                        Matrix::Attract => format!("{:.3}", value),
+                        _ => format!("{:.0}", value),
+                    };
+                    d.draw_text(&text, cx + 2, cy + cell / 2 - 4, FONT_SIZE, Color::WHITE);
+                }
+            }
+        }
+
+        if let Some((i, j)) = layout.cell_at(mouse.0, mouse.1) {
+            d.draw_rectangle_lines(
+                layout.x + j as i32 * cell,
+                layout.y + i as i32 * cell,
+                cell,
+                cell,
+                Color::WHITE,
+            );
+        }
+        d.draw_text(
+            "Scroll or click: +, right click: -",
+            LEFT,
+            layout.y + types.len() as i32 * cell + 4,
+            FONT_SIZE,
+            Color::GRAY,
+        );
+    }
+}
+
+impl Default for MatrixEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where the parts of the editor are on screen for a number of types.
+struct Layout {
+    len: usize,
+    cell: i32,
+    x: i32,
+    y: i32,
+}
+
+impl Layout {
+    fn new(len: usize) -> Self {
+        let cell = (MAX_SIZE / (len as i32 + 1)).clamp(2, MAX_CELL);
+        Layout {
+            len,
+            cell,
+            x: LEFT + cell,
+            y: TOP + TAB_HEIGHT + cell,
+        }
+    }
+
+    fn width(&self) -> i32 {
+        (self.cell * (self.len as i32 + 1)).max(TAB_WIDTH * MATRICES.len() as i32)
+    }
+
+    fn height(&self) -> i32 {
+        TAB_HEIGHT + self.cell * (self.len as i32 + 1) + FONT_SIZE + 4
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        x >= LEFT - 5 && x < LEFT + self.width() + 5 && y >= TOP - 5 && y < TOP + self.height() + 5
+    }
+
+    fn tab_at(&self, x: i32, y: i32) -> Option<Matrix> {
+        if !(TOP..TOP + TAB_HEIGHT).contains(&y) || x < LEFT {
+            return None;
+        }
+        MATRICES.get(((x - LEFT) / TAB_WIDTH) as usize).copied()
+    }
+
+    fn cell_at(&self, x: i32, y: i32) -> Option<(usize, usize)> {
+        if x < self.x || y < self.y {
+            return None;
+        }
+        let (j, i) = (
+            ((x - self.x) / self.cell) as usize,
+            ((y - self.y) / self.cell) as usize,
+        );
+        if i < self.len && j < self.len {
+            Some((i, j))
+        } else {
+            None
+        }
+    }
+}
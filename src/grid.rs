@@ -0,0 +1,229 @@
+use crate::particle::Particle;
+
+const MAX_CELLS: usize = 1024;
+
+/// A uniform grid of cells at least `cell_size` wide, used to find all
+/// particles that could be within interaction range of a point.
+pub struct Grid {
+    cols: usize,
+    rows: usize,
+    cell_w: f32,
+    cell_h: f32,
+    cell_start: Vec<usize>,
+    indices: Vec<usize>,
+}
+
+impl Grid {
+    pub fn new() -> Self {
+        Grid {
+            cols: 1,
+            rows: 1,
+            cell_w: 0.0,
+            cell_h: 0.0,
+            cell_start: vec![0; 2],
+            indices: Vec::new(),
+        }
+    }
+
+    /// Bucket the particles into cells no smaller than `cell_size`.
+    pub fn rebuild(&mut self, particles: &[Particle], width: f32, height: f32, cell_size: f32) {
+        self.cols = Self::cells_along(width, cell_size);
+        self.rows = Self::cells_along(height, cell_size);
+        self.cell_w = width / self.cols as f32;
+        self.cell_h = height / self.rows as f32;
+
+        let num_cells = self.cols * self.rows;
+        self.cell_start.clear();
+        self.cell_start.resize(num_cells + 1, 0);
+        for p in particles.iter() {
+            let c = self.cell_of(p.x, p.y);
+            self.cell_start[c + 1] += 1;
+        }
+        for c in 0..num_cells {
+            self.cell_start[c + 1] += self.cell_start[c];
+        }
+
+        let mut next = self.cell_start.clone();
+        self.indices.clear();
+        self.indices.resize(particles.len(), 0);
+        for (i, p) in particles.iter().enumerate() {
+            let c = self.cell_of(p.x, p.y);
+            self.indices[next[c]] = i;
+            next[c] += 1;
+        }
+    }
+
+    /// Fill `out` with the indices of every particle in the cells surrounding
+    /// `(x, y)`, in ascending order so that forces are summed in the same order
+    /// as a full pass over the particles would.
+    pub fn neighbours(&self, x: f32, y: f32, wrap: bool, out: &mut Vec<usize>) {
+        out.clear();
+        let (col, row) = self.col_row(x, y);
+        for dr in -1..=1 {
+            let r = match self.offset(row, dr, self.rows, wrap) {
+                Some(r) => r,
+                None => continue,
+            };
+            for dc in -1..=1 {
+                let c = match self.offset(col, dc, self.cols, wrap) {
+                    Some(c) => c,
+                    None => continue,
+                };
+                let cell = r * self.cols + c;
+                out.extend_from_slice(
+                    &self.indices[self.cell_start[cell]..self.cell_start[cell + 1]],
+                );
+            }
+        }
+        out.sort_unstable();
+        out.dedup();
+    }
+
+    fn cells_along(length: f32, cell_size: f32) -> usize {
+        if cell_size > 0.0 {
+            ((length / cell_size) as usize).clamp(1, MAX_CELLS)
+        } else {
+            MAX_CELLS
+        }
+    }
+
+    fn offset(&self, i: usize, d: isize, len: usize, wrap: bool) -> Option<usize> {
+        let j = i as isize + d;
+        if wrap {
+            Some(j.rem_euclid(len as isize) as usize)
+        } else if j < 0 || j >= len as isize {
+            None
+        } else {
+            Some(j as usize)
+        }
+    }
+
+    fn col_row(&self, x: f32, y: f32) -> (usize, usize) {
+        let col = ((x / self.cell_w).max(0.0) as usize).min(self.cols - 1);
+        let row = ((y / self.cell_h).max(0.0) as usize).min(self.rows - 1);
+        (col, row)
+    }
+
+    fn cell_of(&self, x: f32, y: f32) -> usize {
+        let (col, row) = self.col_row(x, y);
+        row * self.cols + col
+    }
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    fn particle(x: f32, y: f32) -> Particle {
+        Particle {
+            x,
+            y,
+            ..Default::default()
+        }
+    }
+
+    fn scattered(width: f32, height: f32, count: usize, seed: u64) -> Vec<Particle> {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        (0..count)
+            .map(|_| particle(rng.gen_range(0.0..width), rng.gen_range(0.0..height)))
+            .collect()
+    }
+
+    /// Check every particle's neighbours against a scan over all the others.
+    fn check(particles: &[Particle], width: f32, height: f32, range: f32, wrap: bool) {
+        let mut grid = Grid::new();
+        grid.rebuild(particles, width, height, range);
+        let distance = |d: f32, size: f32| {
+            if wrap {
+                d - size * (d / size).round()
+            } else {
+                d
+            }
+        };
+        let mut out = Vec::new();
+        for p in particles {
+            grid.neighbours(p.x, p.y, wrap, &mut out);
+            assert!(out.windows(2).all(|w| w[0] < w[1]), "not sorted: {:?}", out);
+            for (j, q) in particles.iter().enumerate() {
+                let dx = distance(q.x - p.x, width);
+                let dy = distance(q.y - p.y, height);
+                if dx * dx + dy * dy < range * range {
+                    assert!(
+                        out.contains(&j),
+                        "({}, {}) missed ({}, {}) in a {}x{} world with range {}, wrap {}",
+                        p.x,
+                        p.y,
+                        q.x,
+                        q.y,
+                        width,
+                        height,
+                        range,
+                        wrap
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn finds_every_particle_in_range() {
+        for &wrap in &[false, true] {
+            let particles = scattered(400.0, 300.0, 500, 1);
+            check(&particles, 400.0, 300.0, 30.0, wrap);
+            check(&particles, 400.0, 300.0, 77.0, wrap);
+        }
+    }
+
+    #[test]
+    fn finds_particles_across_the_seam() {
+        let (width, height) = (400.0, 300.0);
+        let mut particles = vec![
+            particle(1.0, 150.0),
+            particle(width - 1.0, 150.0),
+            particle(200.0, 0.5),
+            particle(200.0, height - 0.5),
+            particle(0.0, 0.0),
+            particle(width - 2.0, height - 2.0),
+        ];
+        particles.extend(scattered(width, height, 200, 2));
+        for &wrap in &[false, true] {
+            check(&particles, width, height, 30.0, wrap);
+        }
+    }
+
+    #[test]
+    fn handles_worlds_under_three_cells_wide() {
+        for &(width, height) in &[(50.0, 50.0), (100.0, 40.0), (130.0, 300.0)] {
+            let particles = scattered(width, height, 100, 3);
+            for &wrap in &[false, true] {
+                check(&particles, width, height, 60.0, wrap);
+            }
+        }
+    }
+
+    #[test]
+    fn handles_particles_on_the_far_edges() {
+        let (width, height) = (400.0, 300.0);
+        let mut particles = vec![
+            particle(width, 150.0),
+            particle(0.0, 150.0),
+            particle(width - 5.0, 150.0),
+            particle(200.0, height),
+            particle(200.0, 0.0),
+            particle(width, height),
+        ];
+        particles.extend(scattered(width, height, 100, 4));
+        for &wrap in &[false, true] {
+            check(&particles, width, height, 30.0, wrap);
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use raylib::prelude::RaylibDraw;
+
+use crate::{canvas::Canvas, color::Color, math::Vector2};
+
+impl From<Color> for raylib::color::Color {
+    fn from(c: Color) -> Self {
+        raylib::color::Color::new(c.r, c.g, c.b, c.a)
+    }
+}
+
+impl From<raylib::math::Vector2> for Vector2 {
+    fn from(v: raylib::math::Vector2) -> Self {
+        Vector2::new(v.x, v.y)
+    }
+}
+
+impl<D: RaylibDraw> Canvas for D {
+    fn draw_circle(&mut self, x: f32, y: f32, radius: f32, color: Color) {
+        RaylibDraw::draw_circle(
+            self,
+            x as i32,
+            y as i32,
+            radius,
+            raylib::color::Color::from(color),
+        );
+    }
+
+    fn draw_circle_lines(&mut self, x: f32, y: f32, radius: f32, color: Color) {
+        RaylibDraw::draw_circle_lines(
+            self,
+            x as i32,
+            y as i32,
+            radius,
+            raylib::color::Color::from(color),
+        );
+    }
+}
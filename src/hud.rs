@@ -0,0 +1,108 @@
+use std::time::Instant;
+
+use raylib::{
+    color::Color,
+    prelude::{RaylibDraw, RaylibHandle},
+    text::measure_text,
+};
+
+use crate::{camera::Camera, coloring::ColorMode, universe::Universe};
+
+const RIGHT: i32 = 10;
+const TOP: i32 = 40;
+const FONT_SIZE: i32 = 16;
+const LINE_HEIGHT: i32 = 20;
+
+/// A toggleable overlay in the top right of the window with statistics about
+/// the simulation and how it is being shown.
+pub struct Hud {
+    visible: bool,
+    preset: String,
+    fps: u32,
+    steps_per_second: f32,
+    last_step_count: u64,
+    last_time: Instant,
+}
+
+impl Hud {
+    pub fn new(preset: &str) -> Self {
+        Hud {
+            visible: true,
+            preset: preset.to_string(),
+            fps: 0,
+            steps_per_second: 0.0,
+            last_step_count: 0,
+            last_time: Instant::now(),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Set the name of the preset or file the rules came from.
+    pub fn set_preset(&mut self, preset: &str) {
+        self.preset = preset.to_string();
+    }
+
+    /// Measure how fast the simulation is running. Call once per frame.
+    pub fn update(&mut self, rl: &RaylibHandle, universe: &Universe) {
+        self.fps = rl.get_fps();
+        let elapsed = self.last_time.elapsed().as_secs_f32();
+        if elapsed < 0.5 {
+            return;
+        }
+        let steps = universe.step_count().saturating_sub(self.last_step_count);
+        self.steps_per_second = steps as f32 / elapsed;
+        self.last_step_count = universe.step_count();
+        self.last_time = Instant::now();
+    }
+
+    pub fn draw(
+        &self,
+        d: &mut impl RaylibDraw,
+        universe: &Universe,
+        cam: &Camera,
+        color_mode: ColorMode,
+        width: i32,
+    ) {
+        if !self.visible {
+            return;
+        }
+        let types = universe.types();
+        let friction = (0..types.len()).map(|i| *types.friction(i).unwrap());
+        let min_friction = friction.clone().fold(f32::INFINITY, f32::min);
+        let max_friction = friction.fold(f32::NEG_INFINITY, f32::max);
+        let lines = [
+            format!("FPS: {}", self.fps),
+            format!("Steps/s: {:.0}", self.steps_per_second),
+            format!("Particles: {}", universe.particles().len()),
+            format!("Types: {}", types.len()),
+            format!("Preset: {}", self.preset),
+            format!("Boundary: {}", universe.boundary().name()),
+            format!("Kernel: {}", universe.kernel().name()),
+            format!(
+                "Integrator: {} (dt {})",
+                universe.integrator().name(),
+                universe.dt()
+            ),
+            format!("Friction: {:.3} - {:.3}", min_friction, max_friction),
+            format!("Colours: {}", color_mode.name()),
+            format!("Zoom: {:.2}x", cam.zoom()),
+            match cam.track_index() {
+                Some(i) => format!("Tracking: {}", i),
+                None => "Tracking: none".to_string(),
+            },
+            format!("Kinetic energy: {:.2}", universe.kinetic_energy()),
+        ];
+        for (i, line) in lines.iter().enumerate() {
+            d.draw_text(
+                line,
+                width - RIGHT - measure_text(line, FONT_SIZE),
+                TOP + i as i32 * LINE_HEIGHT,
+                FONT_SIZE,
+                Color::LIGHTGRAY,
+            );
+        }
+    }
+}
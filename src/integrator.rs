@@ -0,0 +1,46 @@
+/// How the universe moves particles forward by one timestep.
+///
+/// Friction is applied separately after each step, so the integrators only
+/// have to deal with the forces between particles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Integrator {
+    /// Kick the velocities with the forces, then move by the new velocities.
+    /// Cheap and stable, and what the universe has always used.
+    #[default]
+    SemiImplicitEuler,
+    /// Second order and time-reversible. Evaluates the forces twice per step.
+    VelocityVerlet,
+    /// The classic fourth order Runge-Kutta method. Evaluates the forces four
+    /// times per step.
+    Rk4,
+}
+
+pub const INTEGRATORS: [Integrator; 3] = [
+    Integrator::SemiImplicitEuler,
+    Integrator::VelocityVerlet,
+    Integrator::Rk4,
+];
+
+impl Integrator {
+    /// A short lowercase name used to select the integrator and to save it in rules.
+    pub fn name(self) -> &'static str {
+        match self {
+            Integrator::SemiImplicitEuler => "semi-implicit-euler",
+            Integrator::VelocityVerlet => "velocity-verlet",
+            Integrator::Rk4 => "rk4",
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        INTEGRATORS
+            .iter()
+            .copied()
+            .find(|i| i.name().eq_ignore_ascii_case(name))
+    }
+
+    /// The integrator after this one, wrapping around to the first.
+    pub fn next(self) -> Self {
+        let i = INTEGRATORS.iter().position(|&i| i == self).unwrap();
+        INTEGRATORS[(i + 1) % INTEGRATORS.len()]
+    }
+}
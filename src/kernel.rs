@@ -0,0 +1,228 @@
+use std::sync::RwLock;
+
+const R_SMOOTH: f32 = 2.0;
+
+/// The interaction parameters between two particle types.
+#[derive(Debug, Clone, Copy)]
+pub struct PairRule {
+    pub attract: f32,
+    pub min_r: f32,
+    pub max_r: f32,
+}
+
+/// A force law between pairs of particles.
+///
+/// The universe only asks for the force between particles closer than
+/// `max_r`, so kernels do not need to cut off beyond it themselves.
+pub trait ForceKernel: Sync {
+    /// A short lowercase name used to select the kernel and to save it in
+    /// rules. Each kernel needs a name of its own.
+    fn name(&self) -> &'static str;
+
+    /// The force on a particle of type `p_type` from one of type `q_type` that
+    /// is `r` away. Positive values pull the particles together.
+    fn force(&self, p_type: usize, q_type: usize, r: f32, rule: &PairRule) -> f32;
+}
+
+/// The repulsion used by most kernels when particles are closer than `min_r`.
+pub fn smooth_repulsion(r: f32, min_r: f32) -> f32 {
+    R_SMOOTH * min_r * (1.0 / (min_r + R_SMOOTH) - 1.0 / (r + R_SMOOTH))
+}
+
+/// A force that rises linearly from zero at `min_r` to `attract` halfway to
+/// `max_r` and falls back to zero at `max_r`.
+pub struct Tent;
+
+impl ForceKernel for Tent {
+    fn name(&self) -> &'static str {
+        "tent"
+    }
+
+    fn force(&self, _: usize, _: usize, r: f32, rule: &PairRule) -> f32 {
+        if r > rule.min_r {
+            let numer = 2.0 * (r - 0.5 * (rule.max_r + rule.min_r)).abs();
+            let denom = rule.max_r - rule.min_r;
+            rule.attract * (1.0 - (numer / denom))
+        } else {
+            smooth_repulsion(r, rule.min_r)
+        }
+    }
+}
+
+/// A constant `attract` between `min_r` and `max_r`.
+pub struct Flat;
+
+impl ForceKernel for Flat {
+    fn name(&self) -> &'static str {
+        "flat"
+    }
+
+    fn force(&self, _: usize, _: usize, r: f32, rule: &PairRule) -> f32 {
+        if r > rule.min_r {
+            rule.attract
+        } else {
+            smooth_repulsion(r, rule.min_r)
+        }
+    }
+}
+
+/// The tent, but with a repulsion that falls linearly from -1 when touching
+/// to zero at `min_r`, as in Clusters.
+pub struct LinearRamp;
+
+impl ForceKernel for LinearRamp {
+    fn name(&self) -> &'static str {
+        "linear-ramp"
+    }
+
+    fn force(&self, p_type: usize, q_type: usize, r: f32, rule: &PairRule) -> f32 {
+        if r > rule.min_r {
+            Tent.force(p_type, q_type, r, rule)
+        } else {
+            r / rule.min_r - 1.0
+        }
+    }
+}
+
+/// A Lennard-Jones force with its equilibrium at `min_r` and a tail scaled by
+/// `attract`, so negative values give a purely repulsive interaction.
+pub struct LennardJones;
+
+impl ForceKernel for LennardJones {
+    fn name(&self) -> &'static str {
+        "lennard-jones"
+    }
+
+    fn force(&self, _: usize, _: usize, r: f32, rule: &PairRule) -> f32 {
+        let sigma = rule.min_r / 2.0_f32.powf(1.0 / 6.0);
+        // Don't let the r^-12 core blow up when particles overlap.
+        let r = r.max(0.9 * sigma);
+        let s6 = (sigma / r).powi(6);
+        24.0 / r * (rule.attract * s6 - 2.0 * rule.attract.abs() * s6 * s6)
+    }
+}
+
+/// A bell curve of `attract` centred between `min_r` and `max_r`.
+pub struct Gaussian;
+
+impl ForceKernel for Gaussian {
+    fn name(&self) -> &'static str {
+        "gaussian"
+    }
+
+    fn force(&self, _: usize, _: usize, r: f32, rule: &PairRule) -> f32 {
+        if r > rule.min_r {
+            let mean = 0.5 * (rule.min_r + rule.max_r);
+            let width = 0.25 * (rule.max_r - rule.min_r);
+            let z = (r - mean) / width;
+            rule.attract * (-z * z).exp()
+        } else {
+            smooth_repulsion(r, rule.min_r)
+        }
+    }
+}
+
+/// `attract` at `min_r`, falling off with the square of the distance.
+pub struct InverseSquare;
+
+impl ForceKernel for InverseSquare {
+    fn name(&self) -> &'static str {
+        "inverse-square"
+    }
+
+    fn force(&self, _: usize, _: usize, r: f32, rule: &PairRule) -> f32 {
+        if r > rule.min_r {
+            let s = rule.min_r / r;
+            rule.attract * s * s
+        } else {
+            smooth_repulsion(r, rule.min_r)
+        }
+    }
+}
+
+pub const KERNELS: [&dyn ForceKernel; 6] = [
+    &Tent,
+    &Flat,
+    &LinearRamp,
+    &LennardJones,
+    &Gaussian,
+    &InverseSquare,
+];
+
+/// Kernels from other crates, in the order they were registered.
+static REGISTERED: RwLock<Vec<&'static dyn ForceKernel>> = RwLock::new(Vec::new());
+
+/// Make a kernel from another crate known by its name, so `by_name` finds it
+/// and rules, snapshots and world codes that use it can be loaded. Returns
+/// false without registering it if a kernel with that name already exists.
+///
+/// `Universe::set_kernel` registers the kernels it is given.
+pub fn register(kernel: &'static dyn ForceKernel) -> bool {
+    let mut registered = REGISTERED.write().unwrap();
+    let taken = KERNELS
+        .iter()
+        .chain(registered.iter())
+        .any(|k| k.name().eq_ignore_ascii_case(kernel.name()));
+    if !taken {
+        registered.push(kernel);
+    }
+    !taken
+}
+
+/// The built-in kernels followed by the registered ones.
+pub fn all() -> Vec<&'static dyn ForceKernel> {
+    let registered = REGISTERED.read().unwrap();
+    KERNELS.iter().chain(registered.iter()).copied().collect()
+}
+
+pub fn by_name(name: &str) -> Option<&'static dyn ForceKernel> {
+    all()
+        .into_iter()
+        .find(|k| k.name().eq_ignore_ascii_case(name))
+}
+
+/// The kernel after `kernel`, including registered ones, wrapping around to
+/// the first.
+pub fn next(kernel: &dyn ForceKernel) -> &'static dyn ForceKernel {
+    let kernels = all();
+    let i = kernels
+        .iter()
+        .position(|k| k.name() == kernel.name())
+        .map_or(0, |i| i + 1);
+    kernels[i % kernels.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rules::Rules, universe::Universe};
+
+    struct Spring;
+
+    impl ForceKernel for Spring {
+        fn name(&self) -> &'static str {
+            "test-spring"
+        }
+
+        fn force(&self, _: usize, _: usize, r: f32, rule: &PairRule) -> f32 {
+            rule.attract * (r - rule.min_r)
+        }
+    }
+
+    #[test]
+    fn custom_kernels_can_be_saved_and_loaded() {
+        let mut universe = Universe::new(3, 10, 200.0, 200.0, 1);
+        universe.set_kernel(&Spring);
+        assert!(!register(&Spring));
+        assert_eq!(by_name("test-spring").unwrap().name(), "test-spring");
+        assert_eq!(next(&InverseSquare).name(), "test-spring");
+        assert_eq!(next(&Spring).name(), Tent.name());
+
+        let rules: Rules = toml::from_str(&universe.rules().to_toml().unwrap()).unwrap();
+        rules.validate().unwrap();
+        assert_eq!(rules.kernel, "test-spring");
+        let mut loaded = Universe::new(1, 0, 100.0, 100.0, 0);
+        loaded.set_rules(&rules).unwrap();
+        assert_eq!(loaded.kernel().name(), "test-spring");
+    }
+}
@@ -0,0 +1,48 @@
+use raylib::{color::Color, prelude::RaylibDraw};
+
+use crate::coloring::{scale_color, Coloring, Legend};
+
+const LEFT: i32 = 10;
+const BOTTOM: i32 = 10;
+const BAR_WIDTH: i32 = 200;
+const BAR_HEIGHT: i32 = 12;
+const FONT_SIZE: i32 = 10;
+
+/// Explain the colours of `coloring` in the bottom left corner of a window
+/// `height` pixels high. Type colours need no explanation.
+pub fn draw_legend(d: &mut impl RaylibDraw, coloring: &Coloring, height: i32) {
+    let top = height - BOTTOM - 2 * FONT_SIZE - BAR_HEIGHT - 6;
+    match coloring.legend {
+        Legend::Types => (),
+        Legend::Scale { label, min, max } => {
+            d.draw_text(label, LEFT, top, FONT_SIZE, Color::LIGHTGRAY);
+            let bar_top = top + FONT_SIZE + 4;
+            for x in 0..BAR_WIDTH {
+                let color = scale_color(x as f32 / (BAR_WIDTH - 1) as f32);
+                d.draw_rectangle(LEFT + x, bar_top, 1, BAR_HEIGHT, Color::from(color));
+            }
+            d.draw_text(
+                &format!("{:.2}", min),
+                LEFT,
+                bar_top + BAR_HEIGHT + 2,
+                FONT_SIZE,
+                Color::LIGHTGRAY,
+            );
+            let max = format!("{:.2}", max);
+            d.draw_text(
+                &max,
+                LEFT + BAR_WIDTH - raylib::text::measure_text(&max, FONT_SIZE),
+                bar_top + BAR_HEIGHT + 2,
+                FONT_SIZE,
+                Color::LIGHTGRAY,
+            );
+        }
+        Legend::Clusters(count) => d.draw_text(
+            &format!("{} clusters", count),
+            LEFT,
+            top,
+            FONT_SIZE,
+            Color::LIGHTGRAY,
+        ),
+    }
+}
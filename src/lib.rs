@@ -0,0 +1,37 @@
+//! A particle-based game of life simulation, independent of how it is drawn.
+//!
+//! The window front-end and its raylib glue are behind the `gui` feature.
+
+pub mod boundary;
+#[cfg(feature = "gui")]
+pub mod brush;
+pub mod camera;
+pub mod canvas;
+pub mod capture;
+pub mod color;
+pub mod coloring;
+#[cfg(feature = "gui")]
+pub mod editor;
+mod grid;
+#[cfg(feature = "gui")]
+pub mod gui;
+#[cfg(feature = "gui")]
+pub mod hud;
+pub mod integrator;
+pub mod kernel;
+#[cfg(feature = "gui")]
+pub mod legend;
+pub mod math;
+pub mod particle;
+pub mod preset;
+pub mod raster;
+#[cfg(feature = "gui")]
+pub mod recorder;
+pub mod rules;
+pub mod share;
+pub mod snapshot;
+pub mod svg;
+#[cfg(feature = "gui")]
+pub mod trails;
+pub mod trajectory;
+pub mod universe;
@@ -1,17 +1,47 @@
-use std::time::{Duration, Instant};
+use std::{
+    env, process,
+    time::{Duration, Instant},
+};
 
-use camera::Camera;
+use particle_life::{
+    brush::Brush,
+    camera::Camera,
+    coloring::{ColorMode, Coloring},
+    editor::MatrixEditor,
+    hud::Hud,
+    kernel,
+    legend::draw_legend,
+    preset::{self, Preset},
+    recorder::Recorder,
+    rules::Rules,
+    share::WorldCode,
+    snapshot::Snapshot,
+    svg::{Svg, SvgOptions},
+    trails::Trails,
+    trajectory::TrajectoryRecorder,
+    universe::Universe,
+};
 use raylib::{
     color::Color,
-    prelude::{KeyboardKey, MouseButton, RaylibDraw},
+    prelude::{KeyboardKey, MouseButton, RaylibDraw, RaylibHandle},
+    text::measure_text,
 };
-use universe::Universe;
-mod camera;
-mod particle;
-mod universe;
 
 const WIDTH: i32 = 1600;
 const HEIGHT: i32 = 900;
+const RULES_PATH: &str = "rules.toml";
+const SNAPSHOT_PATH: &str = "snapshot.bin";
+const FRAMES_DIR: &str = "frames";
+const GIF_PATH: &str = "recording.gif";
+const CSV_TRAJECTORY_PATH: &str = "trajectory.csv";
+const BINARY_TRAJECTORY_PATH: &str = "trajectory.bin";
+const DEFAULT_TRAJECTORY_STRIDE: u64 = 10;
+const USAGE: &str = "particle_life [--code <CODE>] [--stride <N>]";
+const MAX_STEPS_PER_FRAME: u32 = 1024;
+const PAN_SPEED: f32 = 10.0;
+/// How far the mouse must move with the left button held before it pans the
+/// view rather than clicking a particle.
+const DRAG_THRESHOLD: f32 = 5.0;
 
 fn min(v1: f32, v2: f32) -> f32 {
     v1.min(v2)
@@ -21,16 +51,187 @@ fn max(v1: f32, v2: f32) -> f32 {
     v1.max(v2)
 }
 
+/// The preset chosen by pressing `key`, if any.
+fn preset_for_key(key: KeyboardKey) -> Option<&'static Preset> {
+    match key {
+        KeyboardKey::KEY_B => Some(&preset::BALANCED),
+        KeyboardKey::KEY_C => Some(&preset::CHAOS),
+        KeyboardKey::KEY_D => Some(&preset::DIVERSITY),
+        KeyboardKey::KEY_F => Some(&preset::FRICTIONLESS),
+        KeyboardKey::KEY_G => Some(&preset::GLIDERS),
+        KeyboardKey::KEY_H => Some(&preset::HOMOGENEITY),
+        KeyboardKey::KEY_L => Some(&preset::LARGE_CLUSTERS),
+        KeyboardKey::KEY_M => Some(&preset::MEDIUM_CLUSTERS),
+        KeyboardKey::KEY_Q => Some(&preset::QUIESCENCE),
+        KeyboardKey::KEY_S => Some(&preset::SMALL_CLUSTERS),
+        KeyboardKey::KEY_X => Some(&preset::MIXED_MASSES),
+        KeyboardKey::KEY_Z => Some(&preset::MIXED_SIZES),
+        _ => None,
+    }
+}
+
+/// The particle type chosen by pressing a number key, counting from one.
+fn type_for_key(key: KeyboardKey) -> Option<usize> {
+    let key = key as u32;
+    let one = KeyboardKey::KEY_ONE as u32;
+    if (one..=KeyboardKey::KEY_NINE as u32).contains(&key) {
+        Some((key - one) as usize)
+    } else {
+        None
+    }
+}
+
+fn load_rules(universe: &mut Universe, hud: &mut Hud, path: &str) {
+    match Rules::load(path).and_then(|rules| universe.set_rules(&rules)) {
+        Ok(()) => {
+            println!("Loaded rules from '{}'", path);
+            hud.set_preset(path);
+        }
+        Err(e) => println!("Could not load rules from '{}': {}", path, e),
+    }
+}
+
+fn load_snapshot(universe: &mut Universe, cam: &mut Camera, hud: &mut Hud, path: &str) {
+    let restored = Snapshot::load(path).and_then(|snapshot| {
+        universe.restore(&snapshot)?;
+        if let Some(camera) = snapshot.camera {
+            *cam = camera;
+        }
+        Ok(())
+    });
+    match restored {
+        Ok(()) => {
+            println!("Loaded snapshot from '{}'", path);
+            hud.set_preset(path);
+        }
+        Err(e) => println!("Could not load snapshot from '{}': {}", path, e),
+    }
+}
+
+/// Start logging the trajectory to `path` every `stride` steps, or stop if
+/// already logging one.
+fn toggle_trajectory(trajectory: &mut Option<TrajectoryRecorder>, path: &str, stride: u64) {
+    if let Some(mut recorder) = trajectory.take() {
+        let path = recorder.path().display().to_string();
+        match recorder.flush() {
+            Ok(()) => println!("Saved {} steps to '{}'", recorder.len(), path),
+            Err(e) => println!("Could not save '{}': {}", path, e),
+        }
+        return;
+    }
+    match TrajectoryRecorder::create(path, stride) {
+        Ok(recorder) => {
+            println!("Logging every {} steps to '{}'", stride, path);
+            *trajectory = Some(recorder);
+        }
+        Err(e) => println!("Could not create '{}': {}", path, e),
+    }
+}
+
+/// Advance the universe a step, logging it if a trajectory is being recorded.
+fn step(universe: &mut Universe, cam: &mut Camera, trajectory: &mut Option<TrajectoryRecorder>) {
+    if universe.step() > 0 {
+        // The followed particle may have been absorbed or moved to another index.
+        *cam.track_index_mut() = None;
+    }
+    if let Some(recorder) = trajectory {
+        if let Err(e) = recorder.record(universe) {
+            println!("Stopped logging the trajectory: {}", e);
+            *trajectory = None;
+        }
+    }
+}
+
+/// Start again from the world code on the clipboard.
+fn paste_world_code(rl: &RaylibHandle, universe: &mut Universe, cam: &mut Camera, hud: &mut Hud) {
+    let loaded = rl
+        .get_clipboard_text()
+        .map_err(|e| e.to_string())
+        .and_then(|text| WorldCode::decode(&text).map_err(|e| e.to_string()))
+        .and_then(|world| universe.load_world_code(&world).map_err(|e| e.to_string()));
+    match loaded {
+        Ok(()) => {
+            println!("Loaded the world code from the clipboard");
+            cam.reset(universe.width(), universe.height());
+            hud.set_preset("world code");
+        }
+        Err(e) => println!("Could not load a world code from the clipboard: {}", e),
+    }
+}
+
+/// The world code passed with `--code`, if any.
+/// Options given on the command line.
+struct Args {
+    /// The world to start from, instead of a random one.
+    world: Option<WorldCode>,
+    /// Steps between trajectory entries.
+    stride: u64,
+}
+
+impl Args {
+    fn parse() -> Self {
+        let mut parsed = Args {
+            world: None,
+            stride: DEFAULT_TRAJECTORY_STRIDE,
+        };
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let value = args.next().unwrap_or_default();
+            let result = match arg.as_str() {
+                "--code" => WorldCode::decode(&value)
+                    .map(|world| parsed.world = Some(world))
+                    .map_err(|e| e.to_string()),
+                "--stride" => match value.parse() {
+                    Ok(stride) if stride > 0 => {
+                        parsed.stride = stride;
+                        Ok(())
+                    }
+                    _ => Err(format!(
+                        "'--stride' must be a positive integer, found '{}'",
+                        value
+                    )),
+                },
+                _ => Err(format!("unknown argument '{}'", arg)),
+            };
+            if let Err(e) = result {
+                eprintln!("error: {}\nUSAGE:\n    {}", e, USAGE);
+                process::exit(2);
+            }
+        }
+        parsed
+    }
+}
+
 fn main() {
-    let mut steps_per_frame = 20;
+    let args = Args::parse();
+    let mut steps_per_frame: u32 = 20;
+    let mut normal_steps_per_frame = steps_per_frame;
+    let mut paused = false;
+    let mut color_mode = ColorMode::default();
     let (mut rl, thread) = raylib::init()
         .size(WIDTH, HEIGHT)
         .title("Hello, World")
         .vsync()
         .build();
-    let mut universe = Universe::new(4, 400, WIDTH as f32, HEIGHT as f32);
-    universe.re_seed(-0.02, 0.06, (0.0, 20.0), (20.0, 70.0), 0.05, false);
-    let mut cam = Camera::new();
+    let seed = rand::random();
+    let mut universe = Universe::new(4, 400, WIDTH as f32, HEIGHT as f32, seed);
+    universe.re_seed(&preset::BALANCED, seed);
+    let mut cam = Camera::new(WIDTH as f32, HEIGHT as f32);
+    let mut editor = MatrixEditor::new();
+    let mut brush = Brush::new();
+    let mut hud = Hud::new(preset::BALANCED.name);
+    if let Some(world) = &args.world {
+        universe
+            .load_world_code(world)
+            .expect("World codes are checked when read");
+        cam.reset(universe.width(), universe.height());
+        hud.set_preset("world code");
+    }
+    let mut trajectory = None;
+    let mut trails =
+        Trails::new(&mut rl, &thread, WIDTH, HEIGHT).expect("Could not create the trails texture");
+    let mut recorder =
+        Recorder::new(&mut rl, &thread).expect("Could not create the recorder texture");
     println! {"
 =========================================================
 
@@ -50,72 +251,182 @@ classes.
             'M' - Randomize (Medium Clusters)
             'Q' - Randomize (Quiescence)
             'S' - Randomize (Small Clusters)
+            'X' - Randomize (Mixed Masses)
+            'Z' - Randomize (Mixed Sizes)
             'W' - Toggle Wrap-Around
+       Ctrl+'C' - Copy a code for the current world to the clipboard
+       Ctrl+'V' - Start again from a world code on the clipboard
+            'A' - Show/hide the interaction matrix editor
+            'V' - Toggle the brush: left drag paints particles, right drag erases them
+        '1'-'9' - Choose the type of particle the brush paints
+            'Y' - Cycle the type of particle the brush paints through all types
+            'E' - Cycle the boundary (wrap, bounce, absorb, soft walls, disc)
+            'K' - Cycle the force kernel
+            'I' - Cycle the integrator
+        '[' ']' - Halve/double the timestep
         Enter - Keep rules, but re-seed particles
         Space - Toggle slow motion
+            'P' - Pause/resume
+            'N' - Pause and advance a single step
+        '+' '-' - Double/halve the steps per frame
             Tab - Print current parameters to console
+            F1 - Show/hide the statistics overlay
+            F5 - Save current rules to 'rules.toml'
+            F6 - Save a snapshot of everything to 'snapshot.bin'
+            F9 - Load rules from 'rules.toml'
+           F10 - Load a snapshot from 'snapshot.bin'
+           F11 - Save the view as an SVG, with the world edges and a legend
+           F12 - Save a screenshot
+            F7 - Start/stop saving every frame into 'frames/'
+            F8 - Start/stop recording 'recording.gif'
+            'J' - Start/stop logging particles every 10 steps to 'trajectory.csv'
+                  (with Shift, to the binary 'trajectory.bin'; start with
+                  '--stride N' to log every N steps)
+   Drop a file - Load rules ('.toml') or a snapshot ('.bin')
     Left Click - Click a particle to follow it
     Right Click - Click anywhere to unfollow particle
+    Left/Middle Drag - Pan the view
+     Arrow Keys - Pan the view
+            'R' - Reset the view
+            'T' - Toggle particle trails
+            'O' - Colour by type, speed, density, neighbours or cluster
+        ',' '.' - Shorten/lengthen the trails
+            'U' - Toggle an unbounded view that can zoom out to tiled copies of the world
 Scroll Wheel - Zoom in/out
 "};
 
+    let mut last_mouse = rl.get_mouse_position();
+    let mut left_press = last_mouse;
+    let mut left_dragging = false;
     while !rl.window_should_close() {
+        let mouse = rl.get_mouse_position();
+        let (mouse_dx, mouse_dy) = (mouse.x - last_mouse.x, mouse.y - last_mouse.y);
+        last_mouse = mouse;
+        if rl.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON) {
+            left_press = mouse;
+            left_dragging = false;
+        } else if rl.is_mouse_button_down(MouseButton::MOUSE_LEFT_BUTTON) && !left_dragging {
+            let (dx, dy) = (mouse.x - left_press.x, mouse.y - left_press.y);
+            left_dragging = dx * dx + dy * dy > DRAG_THRESHOLD * DRAG_THRESHOLD;
+        }
+        let ctrl = rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+            || rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
         match rl.get_key_pressed() {
-            Some(KeyboardKey::KEY_B) => {
-                universe.set_population(9, 400);
-                universe.re_seed(-0.02, 0.06, (0.0, 20.0), (20.0, 70.0), 0.05, false);
-            }
-            Some(KeyboardKey::KEY_C) => {
-                universe.set_population(6, 400);
-                universe.re_seed(0.02, 0.04, (0.0, 30.0), (30.0, 100.0), 0.01, false);
-            }
-            Some(KeyboardKey::KEY_D) => {
-                universe.set_population(12, 400);
-                universe.re_seed(-0.01, 0.04, (0.0, 20.0), (10.0, 60.0), 0.05, true);
+            Some(KeyboardKey::KEY_C) if ctrl => {
+                let code = WorldCode::new(&universe).encode();
+                match rl.set_clipboard_text(&code) {
+                    Ok(()) => println!("Copied the world code to the clipboard:\n{}", code),
+                    Err(e) => println!("Could not copy the world code: {}", e),
+                }
             }
-            Some(KeyboardKey::KEY_F) => {
-                universe.set_population(6, 300);
-                universe.re_seed(0.01, 0.05, (10.0, 10.0), (10.0, 60.0), 0.0, true);
+            Some(KeyboardKey::KEY_V) if ctrl => {
+                paste_world_code(&rl, &mut universe, &mut cam, &mut hud)
             }
-            Some(KeyboardKey::KEY_G) => {
-                universe.set_population(6, 400);
-                universe.re_seed(0.0, 0.06, (0.0, 20.0), (10.0, 50.0), 0.1, true);
+            Some(key) if preset_for_key(key).is_some() => {
+                let preset = preset_for_key(key).unwrap();
+                universe.apply_preset(preset, rand::random());
+                hud.set_preset(preset.name);
             }
-            Some(KeyboardKey::KEY_H) => {
-                universe.set_population(4, 400);
-                universe.re_seed(0.0, 0.04, (10.0, 10.0), (10.0, 80.0), 0.05, true);
+            Some(KeyboardKey::KEY_W) => universe.toggle_wrap(),
+            Some(KeyboardKey::KEY_A) => editor.toggle(),
+            Some(KeyboardKey::KEY_V) => brush.toggle(),
+            Some(KeyboardKey::KEY_T) => trails.toggle(),
+            Some(KeyboardKey::KEY_O) => color_mode = color_mode.next(),
+            Some(KeyboardKey::KEY_COMMA) => trails.set_fade(trails.fade() * 2.0),
+            Some(KeyboardKey::KEY_PERIOD) => trails.set_fade(trails.fade() * 0.5),
+            Some(KeyboardKey::KEY_U) => universe.toggle_unbounded_view(),
+            Some(KeyboardKey::KEY_R) => cam.reset(universe.width(), universe.height()),
+            Some(key) if type_for_key(key).is_some() => brush.set_type(type_for_key(key).unwrap()),
+            Some(KeyboardKey::KEY_Y) => brush.next_type(universe.types().len()),
+            Some(KeyboardKey::KEY_F1) => hud.toggle(),
+            Some(KeyboardKey::KEY_E) => {
+                universe.set_boundary(universe.boundary().next());
+                println!("Boundary: {}", universe.boundary().name());
             }
-            Some(KeyboardKey::KEY_L) => {
-                universe.set_population(6, 400);
-                universe.re_seed(0.025, 0.02, (0.0, 30.0), (30.0, 100.0), 0.2, false);
+            Some(KeyboardKey::KEY_K) => {
+                universe.set_kernel(kernel::next(universe.kernel()));
+                println!("Kernel: {}", universe.kernel().name());
             }
-            Some(KeyboardKey::KEY_M) => {
-                universe.set_population(6, 400);
-                universe.re_seed(0.02, 0.05, (0.0, 20.0), (20.0, 50.0), 0.05, false);
+            Some(KeyboardKey::KEY_I) => {
+                universe.set_integrator(universe.integrator().next());
+                println!("Integrator: {}", universe.integrator().name());
             }
-            Some(KeyboardKey::KEY_Q) => {
-                universe.set_population(6, 300);
-                universe.re_seed(-0.02, 0.1, (10.0, 20.0), (20.0, 60.0), 0.2, false);
+            Some(KeyboardKey::KEY_LEFT_BRACKET) => {
+                universe.set_dt(universe.dt() * 0.5);
+                println!("Timestep: {}", universe.dt());
             }
-            Some(KeyboardKey::KEY_S) => {
-                universe.set_population(6, 300);
-                universe.re_seed(-0.005, 0.01, (10.0, 10.0), (20.0, 50.0), 0.01, false);
+            Some(KeyboardKey::KEY_RIGHT_BRACKET) => {
+                universe.set_dt(universe.dt() * 2.0);
+                println!("Timestep: {}", universe.dt());
             }
-            Some(KeyboardKey::KEY_W) => universe.toggle_wrap(),
             Some(KeyboardKey::KEY_ENTER) => universe.set_random_particles(),
             Some(KeyboardKey::KEY_TAB) => println!("{}", &universe),
+            Some(KeyboardKey::KEY_F5) => match universe.rules().save(RULES_PATH) {
+                Ok(()) => println!("Saved rules to '{}'", RULES_PATH),
+                Err(e) => println!("Could not save rules to '{}': {}", RULES_PATH, e),
+            },
+            Some(KeyboardKey::KEY_F6) => match universe.snapshot(Some(&cam)).save(SNAPSHOT_PATH) {
+                Ok(()) => println!("Saved snapshot to '{}'", SNAPSHOT_PATH),
+                Err(e) => println!("Could not save snapshot to '{}': {}", SNAPSHOT_PATH, e),
+            },
+            Some(KeyboardKey::KEY_F9) => load_rules(&mut universe, &mut hud, RULES_PATH),
+            Some(KeyboardKey::KEY_F10) => {
+                load_snapshot(&mut universe, &mut cam, &mut hud, SNAPSHOT_PATH)
+            }
+            Some(KeyboardKey::KEY_F12) => {
+                recorder.request_screenshot(format!("screenshot_{}.png", universe.step_count()))
+            }
+            Some(KeyboardKey::KEY_F11) => {
+                let path = format!("view_{}.svg", universe.step_count());
+                let options = SvgOptions {
+                    boundary: true,
+                    legend: true,
+                    ..SvgOptions::default()
+                };
+                let coloring = Coloring::new(&universe, color_mode);
+                match Svg::render(&universe, &coloring, &options).save(&path) {
+                    Ok(()) => println!("Saved the view to '{}'", path),
+                    Err(e) => println!("Could not save the view to '{}': {}", path, e),
+                }
+            }
+            Some(KeyboardKey::KEY_F7) => recorder.toggle_png_sequence(FRAMES_DIR),
+            Some(KeyboardKey::KEY_F8) => recorder.toggle_gif(GIF_PATH),
+            Some(KeyboardKey::KEY_J) => {
+                let path = if rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
+                    || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT)
+                {
+                    BINARY_TRAJECTORY_PATH
+                } else {
+                    CSV_TRAJECTORY_PATH
+                };
+                toggle_trajectory(&mut trajectory, path, args.stride);
+            }
             Some(KeyboardKey::KEY_SPACE) => {
                 if steps_per_frame == 1 {
-                    steps_per_frame = 20
+                    steps_per_frame = normal_steps_per_frame
                 } else {
+                    normal_steps_per_frame = steps_per_frame;
                     steps_per_frame = 1
                 }
             }
+            Some(KeyboardKey::KEY_P) => paused = !paused,
+            Some(KeyboardKey::KEY_N) => {
+                paused = true;
+                step(&mut universe, &mut cam, &mut trajectory);
+            }
+            Some(KeyboardKey::KEY_EQUAL) | Some(KeyboardKey::KEY_KP_ADD) => {
+                steps_per_frame = (steps_per_frame * 2).min(MAX_STEPS_PER_FRAME)
+            }
+            Some(KeyboardKey::KEY_MINUS) | Some(KeyboardKey::KEY_KP_SUBTRACT) => {
+                steps_per_frame = (steps_per_frame / 2).max(1)
+            }
+            _ if editor.update(&rl, &mut universe) => {}
+            _ if brush.update(&rl, &mut universe, &mut cam) => {}
             _ => {
                 let mouse_pos = rl.get_mouse_position();
                 if rl.get_mouse_wheel_move() != 0.0 {
                     *cam.zoom_dest_mut() *= 1.1_f32.powf(rl.get_mouse_wheel_move());
-                    *cam.zoom_dest_mut() = max(min(cam.zoom_dest(), 10.0), 1.0);
+                    *cam.zoom_dest_mut() = max(min(cam.zoom_dest(), 10.0), universe.min_zoom());
                     let cur_time = Instant::now();
                     if cur_time.duration_since(cam.last_scroll_time()) > Duration::from_millis(300)
                     {
@@ -125,20 +436,87 @@ Scroll Wheel - Zoom in/out
                     *cam.track_index_mut() =
                         universe.get_index(mouse_pos.x as usize, mouse_pos.y as usize);
                 } else if rl.is_mouse_button_pressed(MouseButton::MOUSE_RIGHT_BUTTON) {
-                    *cam.x_dest_mut() = (WIDTH / 2) as f32;
-                    *cam.y_dest_mut() = (HEIGHT / 2) as f32;
+                    *cam.x_dest_mut() = universe.width() / 2.0;
+                    *cam.y_dest_mut() = universe.height() / 2.0;
                     *cam.track_index_mut() = None;
                 }
+                if (rl.is_mouse_button_down(MouseButton::MOUSE_MIDDLE_BUTTON)
+                    || (left_dragging && rl.is_mouse_button_down(MouseButton::MOUSE_LEFT_BUTTON)))
+                    && (mouse_dx != 0.0 || mouse_dy != 0.0)
+                {
+                    cam.drag(-mouse_dx / cam.zoom(), -mouse_dy / cam.zoom());
+                }
+            }
+        }
+        let pan = PAN_SPEED / cam.zoom();
+        for (key, dx, dy) in [
+            (KeyboardKey::KEY_LEFT, -pan, 0.0),
+            (KeyboardKey::KEY_RIGHT, pan, 0.0),
+            (KeyboardKey::KEY_UP, 0.0, -pan),
+            (KeyboardKey::KEY_DOWN, 0.0, pan),
+        ] {
+            if rl.is_key_down(key) {
+                cam.pan(dx, dy);
+            }
+        }
+        if rl.is_file_dropped() {
+            for path in rl.get_dropped_files() {
+                if path.ends_with(".toml") {
+                    load_rules(&mut universe, &mut hud, &path);
+                } else if path.ends_with(".bin") {
+                    load_snapshot(&mut universe, &mut cam, &mut hud, &path);
+                }
             }
+            rl.clear_dropped_files();
         }
+        *cam.zoom_dest_mut() = max(cam.zoom_dest(), universe.min_zoom());
         cam.apply_zoom(&mut universe);
+        hud.update(&rl, &universe);
 
         let mut d = rl.begin_drawing(&thread);
+        d.clear_background(Color::BLACK);
 
-        for _ in 0..steps_per_frame {
-            universe.step();
+        if !paused {
+            for _ in 0..steps_per_frame {
+                step(&mut universe, &mut cam, &mut trajectory);
+            }
         }
-        universe.draw(&mut d, 1.0);
-        d.clear_background(Color::BLACK);
+        let coloring = Coloring::new(&universe, color_mode);
+        if trails.is_enabled() {
+            trails.draw(&mut d, &thread, &universe, &coloring);
+        } else {
+            universe.draw(&mut d, &coloring, 1.0);
+        }
+        d.draw_text(
+            &format!("Seed: {}", universe.seed()),
+            10,
+            10,
+            20,
+            Color::WHITE,
+        );
+        let speed = if paused {
+            format!("Step {} (paused)", universe.step_count())
+        } else {
+            format!(
+                "Step {} ({} per frame)",
+                universe.step_count(),
+                steps_per_frame
+            )
+        };
+        d.draw_text(
+            &speed,
+            WIDTH - 10 - measure_text(&speed, 20),
+            10,
+            20,
+            Color::WHITE,
+        );
+        if color_mode != ColorMode::Type {
+            draw_legend(&mut d, &coloring, HEIGHT);
+        }
+        hud.draw(&mut d, &universe, &cam, color_mode, WIDTH);
+        brush.draw(&mut d, &universe, (mouse.x as i32, mouse.y as i32));
+        editor.draw(&mut d, &universe, (mouse.x as i32, mouse.y as i32));
+        recorder.capture(&mut d, &thread);
+        recorder.draw(&mut d, WIDTH, HEIGHT);
     }
 }
@@ -1,7 +1,14 @@
-use raylib::color::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::color::Color;
+
+pub const DEFAULT_RADIUS: f32 = 5.0;
 
 pub struct ParticleTypes {
     color: Vec<Color>,
+    friction: Vec<f32>,
+    mass: Vec<f32>,
+    radius: Vec<f32>,
     attract: Vec<f32>,
     min_r: Vec<f32>,
     max_r: Vec<f32>,
@@ -11,6 +18,9 @@ impl ParticleTypes {
     pub fn with_len(len: usize) -> Self {
         ParticleTypes {
             color: vec![Color::BLACK; len],
+            friction: vec![0.0; len],
+            mass: vec![1.0; len],
+            radius: vec![DEFAULT_RADIUS; len],
             attract: vec![0.0; len * len],
             min_r: vec![0.0; len * len],
             max_r: vec![0.0; len * len],
@@ -19,6 +29,9 @@ impl ParticleTypes {
 
     pub fn resize(&mut self, size: usize) {
         self.color.resize(size, Color::BLACK);
+        self.friction.resize(size, 0.0);
+        self.mass.resize(size, 1.0);
+        self.radius.resize(size, DEFAULT_RADIUS);
         self.attract.resize(size * size, 0.0);
         self.min_r.resize(size * size, 0.0);
         self.max_r.resize(size * size, 0.0);
@@ -28,6 +41,10 @@ impl ParticleTypes {
         self.color.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.color.is_empty()
+    }
+
     pub fn color(&self, i: usize) -> Option<&Color> {
         self.color.get(i)
     }
@@ -36,6 +53,35 @@ impl ParticleTypes {
         self.color.get_mut(i)
     }
 
+    pub fn friction(&self, i: usize) -> Option<&f32> {
+        self.friction.get(i)
+    }
+
+    pub fn friction_mut(&mut self, i: usize) -> Option<&mut f32> {
+        self.friction.get_mut(i)
+    }
+
+    pub fn mass(&self, i: usize) -> Option<&f32> {
+        self.mass.get(i)
+    }
+
+    pub fn mass_mut(&mut self, i: usize) -> Option<&mut f32> {
+        self.mass.get_mut(i)
+    }
+
+    pub fn radius(&self, i: usize) -> Option<&f32> {
+        self.radius.get(i)
+    }
+
+    pub fn radius_mut(&mut self, i: usize) -> Option<&mut f32> {
+        self.radius.get_mut(i)
+    }
+
+    /// The distance at which particles of types `i` and `j` touch.
+    pub fn contact_distance(&self, i: usize, j: usize) -> f32 {
+        self.radius[i] + self.radius[j]
+    }
+
     pub fn attract(&self, i: usize, j: usize) -> Option<&f32> {
         self.attract.get(i * self.len() + j)
     }
@@ -62,9 +108,18 @@ impl ParticleTypes {
         let len = self.len();
         self.max_r.get_mut(i * len + j)
     }
+
+    pub fn largest_max_r(&self) -> f32 {
+        self.max_r.iter().fold(0.0, |acc, &r| acc.max(r))
+    }
+
+    /// The strongest attraction or repulsion between any two types.
+    pub fn largest_attract(&self) -> f32 {
+        self.attract.iter().fold(0.0, |acc, &a| acc.max(a.abs()))
+    }
 }
 
-#[derive(Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Particle {
     pub x: f32,
     pub y: f32,
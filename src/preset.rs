@@ -0,0 +1,213 @@
+use crate::{
+    kernel::{Flat, ForceKernel, Tent},
+    particle::DEFAULT_RADIUS,
+};
+
+/// A named set of parameters for generating a random universe.
+pub struct Preset {
+    pub name: &'static str,
+    pub num_types: usize,
+    pub num_particles: usize,
+    pub attract_mean: f32,
+    pub attract_std: f32,
+    pub min_r: (f32, f32),
+    pub max_r: (f32, f32),
+    pub friction: (f32, f32),
+    pub mass: (f32, f32),
+    pub radius: (f32, f32),
+    pub kernel: &'static dyn ForceKernel,
+}
+
+impl Preset {
+    /// Find a preset by name, ignoring case and treating `-` or `_` as spaces.
+    pub fn by_name(name: &str) -> Option<&'static Preset> {
+        let name = name.replace(['-', '_'], " ");
+        PRESETS
+            .iter()
+            .copied()
+            .find(|p| p.name.eq_ignore_ascii_case(&name))
+    }
+}
+
+pub const BALANCED: Preset = Preset {
+    name: "Balanced",
+    num_types: 9,
+    num_particles: 400,
+    attract_mean: -0.02,
+    attract_std: 0.06,
+    min_r: (0.0, 20.0),
+    max_r: (20.0, 70.0),
+    friction: (0.05, 0.05),
+    mass: (1.0, 1.0),
+    radius: (DEFAULT_RADIUS, DEFAULT_RADIUS),
+    kernel: &Tent,
+};
+
+pub const CHAOS: Preset = Preset {
+    name: "Chaos",
+    num_types: 6,
+    num_particles: 400,
+    attract_mean: 0.02,
+    attract_std: 0.04,
+    min_r: (0.0, 30.0),
+    max_r: (30.0, 100.0),
+    friction: (0.01, 0.01),
+    mass: (1.0, 1.0),
+    radius: (DEFAULT_RADIUS, DEFAULT_RADIUS),
+    kernel: &Tent,
+};
+
+pub const DIVERSITY: Preset = Preset {
+    name: "Diversity",
+    num_types: 12,
+    num_particles: 400,
+    attract_mean: -0.01,
+    attract_std: 0.04,
+    min_r: (0.0, 20.0),
+    max_r: (10.0, 60.0),
+    friction: (0.05, 0.05),
+    mass: (1.0, 1.0),
+    radius: (DEFAULT_RADIUS, DEFAULT_RADIUS),
+    kernel: &Flat,
+};
+
+pub const FRICTIONLESS: Preset = Preset {
+    name: "Frictionless",
+    num_types: 6,
+    num_particles: 300,
+    attract_mean: 0.01,
+    attract_std: 0.05,
+    min_r: (10.0, 10.0),
+    max_r: (10.0, 60.0),
+    friction: (0.0, 0.0),
+    mass: (1.0, 1.0),
+    radius: (DEFAULT_RADIUS, DEFAULT_RADIUS),
+    kernel: &Flat,
+};
+
+pub const GLIDERS: Preset = Preset {
+    name: "Gliders",
+    num_types: 6,
+    num_particles: 400,
+    attract_mean: 0.0,
+    attract_std: 0.06,
+    min_r: (0.0, 20.0),
+    max_r: (10.0, 50.0),
+    friction: (0.1, 0.1),
+    mass: (1.0, 1.0),
+    radius: (DEFAULT_RADIUS, DEFAULT_RADIUS),
+    kernel: &Flat,
+};
+
+pub const HOMOGENEITY: Preset = Preset {
+    name: "Homogeneity",
+    num_types: 4,
+    num_particles: 400,
+    attract_mean: 0.0,
+    attract_std: 0.04,
+    min_r: (10.0, 10.0),
+    max_r: (10.0, 80.0),
+    friction: (0.05, 0.05),
+    mass: (1.0, 1.0),
+    radius: (DEFAULT_RADIUS, DEFAULT_RADIUS),
+    kernel: &Flat,
+};
+
+pub const LARGE_CLUSTERS: Preset = Preset {
+    name: "Large Clusters",
+    num_types: 6,
+    num_particles: 400,
+    attract_mean: 0.025,
+    attract_std: 0.02,
+    min_r: (0.0, 30.0),
+    max_r: (30.0, 100.0),
+    friction: (0.2, 0.2),
+    mass: (1.0, 1.0),
+    radius: (DEFAULT_RADIUS, DEFAULT_RADIUS),
+    kernel: &Tent,
+};
+
+pub const MEDIUM_CLUSTERS: Preset = Preset {
+    name: "Medium Clusters",
+    num_types: 6,
+    num_particles: 400,
+    attract_mean: 0.02,
+    attract_std: 0.05,
+    min_r: (0.0, 20.0),
+    max_r: (20.0, 50.0),
+    friction: (0.05, 0.05),
+    mass: (1.0, 1.0),
+    radius: (DEFAULT_RADIUS, DEFAULT_RADIUS),
+    kernel: &Tent,
+};
+
+pub const QUIESCENCE: Preset = Preset {
+    name: "Quiescence",
+    num_types: 6,
+    num_particles: 300,
+    attract_mean: -0.02,
+    attract_std: 0.1,
+    min_r: (10.0, 20.0),
+    max_r: (20.0, 60.0),
+    friction: (0.2, 0.2),
+    mass: (1.0, 1.0),
+    radius: (DEFAULT_RADIUS, DEFAULT_RADIUS),
+    kernel: &Tent,
+};
+
+pub const SMALL_CLUSTERS: Preset = Preset {
+    name: "Small Clusters",
+    num_types: 6,
+    num_particles: 300,
+    attract_mean: -0.005,
+    attract_std: 0.01,
+    min_r: (10.0, 10.0),
+    max_r: (20.0, 50.0),
+    friction: (0.01, 0.01),
+    mass: (1.0, 1.0),
+    radius: (DEFAULT_RADIUS, DEFAULT_RADIUS),
+    kernel: &Tent,
+};
+
+pub const MIXED_MASSES: Preset = Preset {
+    name: "Mixed Masses",
+    num_types: 6,
+    num_particles: 400,
+    attract_mean: -0.01,
+    attract_std: 0.06,
+    min_r: (0.0, 20.0),
+    max_r: (20.0, 70.0),
+    friction: (0.02, 0.1),
+    mass: (0.5, 4.0),
+    radius: (DEFAULT_RADIUS, DEFAULT_RADIUS),
+    kernel: &Tent,
+};
+
+pub const MIXED_SIZES: Preset = Preset {
+    name: "Mixed Sizes",
+    num_types: 6,
+    num_particles: 400,
+    attract_mean: 0.0,
+    attract_std: 0.05,
+    min_r: (0.0, 20.0),
+    max_r: (20.0, 70.0),
+    friction: (0.05, 0.05),
+    mass: (1.0, 1.0),
+    radius: (2.0, 10.0),
+    kernel: &Tent,
+};
+
+pub const PRESETS: [&Preset; 12] = [
+    &BALANCED,
+    &CHAOS,
+    &DIVERSITY,
+    &FRICTIONLESS,
+    &GLIDERS,
+    &HOMOGENEITY,
+    &LARGE_CLUSTERS,
+    &MEDIUM_CLUSTERS,
+    &QUIESCENCE,
+    &SMALL_CLUSTERS,
+    &MIXED_MASSES,
+    &MIXED_SIZES,
+];
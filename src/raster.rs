@@ -0,0 +1,89 @@
+use crate::{canvas::Canvas, color::Color};
+
+/// An RGBA image in memory that the universe can be drawn onto without a
+/// window, for saving frames from the headless runner.
+pub struct Raster {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Raster {
+    pub fn new(width: usize, height: usize) -> Self {
+        let mut raster = Raster {
+            width,
+            height,
+            pixels: vec![0; width * height * 4],
+        };
+        raster.clear(Color::BLACK);
+        raster
+    }
+
+    pub fn clear(&mut self, color: Color) {
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The pixels as rows of RGBA bytes from the top left.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Blend `color` over the pixel at `(x, y)` using its alpha.
+    fn blend(&mut self, x: usize, y: usize, color: Color) {
+        let i = (y * self.width + x) * 4;
+        let a = color.a as u32;
+        for (c, &src) in self.pixels[i..i + 3]
+            .iter_mut()
+            .zip(&[color.r, color.g, color.b])
+        {
+            *c = ((src as u32 * a + *c as u32 * (255 - a)) / 255) as u8;
+        }
+        self.pixels[i + 3] = 255;
+    }
+
+    /// Blend `color` over every pixel whose centre is within the bounds of
+    /// a circle at `(x, y)` and which `inside` accepts given its squared
+    /// distance from the centre.
+    fn fill(&mut self, x: f32, y: f32, radius: f32, color: Color, inside: impl Fn(f32) -> bool) {
+        let clip = |v: f32, len: usize| (v.max(0.0) as usize).min(len);
+        let (left, right) = (
+            clip(x - radius, self.width),
+            clip(x + radius + 1.0, self.width),
+        );
+        let (top, bottom) = (
+            clip(y - radius, self.height),
+            clip(y + radius + 1.0, self.height),
+        );
+        for py in top..bottom {
+            for px in left..right {
+                let (dx, dy) = (px as f32 + 0.5 - x, py as f32 + 0.5 - y);
+                if inside(dx * dx + dy * dy) {
+                    self.blend(px, py, color);
+                }
+            }
+        }
+    }
+}
+
+impl Canvas for Raster {
+    fn draw_circle(&mut self, x: f32, y: f32, radius: f32, color: Color) {
+        self.fill(x, y, radius, color, |d2| d2 <= radius * radius);
+    }
+
+    fn draw_circle_lines(&mut self, x: f32, y: f32, radius: f32, color: Color) {
+        let (inner, outer) = ((radius - 0.5).max(0.0), radius + 0.5);
+        self.fill(x, y, outer, color, |d2| {
+            d2 >= inner * inner && d2 <= outer * outer
+        });
+    }
+}
@@ -0,0 +1,146 @@
+use std::path::{Path, PathBuf};
+
+use raylib::{
+    color::Color,
+    ffi,
+    prelude::{
+        Image, RaylibDraw, RaylibDrawHandle, RaylibHandle, RaylibTextureModeExt, RaylibThread,
+        RenderTexture2D,
+    },
+};
+
+use crate::capture::{save_png, CaptureError, GifRecorder, PngSequence, GIF_DELAY};
+
+/// Saves what is on screen as a single screenshot, a numbered PNG sequence or
+/// an animated GIF.
+pub struct Recorder {
+    /// Drawing into this flushes everything drawn so far onto the screen, so
+    /// it can be read back before the frame ends.
+    flush: RenderTexture2D,
+    screenshot: Option<PathBuf>,
+    pngs: Option<PngSequence>,
+    gif_path: Option<PathBuf>,
+    gif: Option<GifRecorder>,
+}
+
+impl Recorder {
+    pub fn new(rl: &mut RaylibHandle, thread: &RaylibThread) -> Result<Self, String> {
+        Ok(Recorder {
+            flush: rl.load_render_texture(thread, 1, 1)?,
+            screenshot: None,
+            pngs: None,
+            gif_path: None,
+            gif: None,
+        })
+    }
+
+    /// Save the next captured frame to `path`.
+    pub fn request_screenshot(&mut self, path: impl Into<PathBuf>) {
+        self.screenshot = Some(path.into());
+    }
+
+    /// Start writing every frame into `dir`, or stop if already doing so.
+    pub fn toggle_png_sequence(&mut self, dir: impl AsRef<Path>) {
+        if let Some(pngs) = self.pngs.take() {
+            println!(
+                "Saved {} frames to '{}'",
+                pngs.len(),
+                dir.as_ref().display()
+            );
+            return;
+        }
+        match PngSequence::new(dir.as_ref()) {
+            Ok(pngs) => {
+                println!("Recording frames to '{}'", dir.as_ref().display());
+                self.pngs = Some(pngs);
+            }
+            Err(e) => println!("Could not record to '{}': {}", dir.as_ref().display(), e),
+        }
+    }
+
+    /// Start recording every frame into a GIF at `path`, or stop and finish
+    /// the file if already recording.
+    pub fn toggle_gif(&mut self, path: impl Into<PathBuf>) {
+        let path = path.into();
+        if self.gif_path.take().is_some() {
+            if let Some(gif) = self.gif.take() {
+                println!("Saved {} frames to '{}'", gif.len(), path.display());
+            }
+            return;
+        }
+        println!("Recording to '{}'", path.display());
+        self.gif_path = Some(path);
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.pngs.is_some() || self.gif_path.is_some()
+    }
+
+    /// Save everything drawn so far this frame wherever it has been asked
+    /// for. Call after drawing the parts of the frame that should be kept.
+    pub fn capture(&mut self, d: &mut RaylibDrawHandle, thread: &RaylibThread) {
+        if self.screenshot.is_none() && !self.is_recording() {
+            return;
+        }
+        drop(d.begin_texture_mode(thread, &mut self.flush));
+        let (width, height, pixels) = screen_pixels();
+
+        if let Some(path) = self.screenshot.take() {
+            match save_png(&path, width, height, &pixels) {
+                Ok(()) => println!("Saved screenshot to '{}'", path.display()),
+                Err(e) => println!("Could not save '{}': {}", path.display(), e),
+            }
+        }
+        if let Some(pngs) = &mut self.pngs {
+            if let Err(e) = pngs.write(width, height, &pixels) {
+                println!("Stopped recording frames: {}", e);
+                self.pngs = None;
+            }
+        }
+        if let Some(path) = self.gif_path.clone() {
+            if let Err(e) = self.write_gif(&path, width, height, &pixels) {
+                println!("Stopped recording to '{}': {}", path.display(), e);
+                self.gif_path = None;
+                self.gif = None;
+            }
+        }
+    }
+
+    fn write_gif(
+        &mut self,
+        path: &Path,
+        width: usize,
+        height: usize,
+        pixels: &[u8],
+    ) -> Result<(), CaptureError> {
+        if self.gif.is_none() {
+            self.gif = Some(GifRecorder::create(path, width, height, GIF_DELAY)?);
+        }
+        self.gif.as_mut().unwrap().write(pixels)
+    }
+
+    /// Show a marker in the bottom right while recording.
+    pub fn draw(&self, d: &mut impl RaylibDraw, width: i32, height: i32) {
+        if self.is_recording() {
+            d.draw_circle(width - 20, height - 20, 8.0, Color::RED);
+            d.draw_text("REC", width - 64, height - 28, 16, Color::RED);
+        }
+    }
+}
+
+/// The size of the screen and its pixels as rows of RGBA bytes from the top
+/// left.
+fn screen_pixels() -> (usize, usize, Vec<u8>) {
+    // SAFETY: `GetScreenData` returns a newly allocated image that nothing
+    // else owns, so `Image` can free it when dropped. The raw call is needed
+    // because `RaylibDrawHandle` only implements `Deref` to `RaylibHandle`,
+    // not `DerefMut`, so the safe `get_screen_data` cannot be called while a
+    // frame is being drawn.
+    let image = unsafe { Image::from_raw(ffi::GetScreenData()) };
+    let pixels = image
+        .get_image_data()
+        .iter()
+        .flat_map(|c| [c.r, c.g, c.b, c.a])
+        .collect();
+    (image.width() as usize, image.height() as usize, pixels)
+}
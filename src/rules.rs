@@ -0,0 +1,359 @@
+use std::{fmt::Display, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    boundary::Boundary, color::Color, integrator::Integrator, kernel, particle::ParticleTypes,
+};
+
+/// Everything needed to recreate a universe's behaviour, without its particles.
+///
+/// Rules are saved as TOML, with the interaction matrices written one row per
+/// particle type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rules {
+    pub width: f32,
+    pub height: f32,
+    pub boundary: String,
+    pub kernel: String,
+    #[serde(default = "default_integrator")]
+    pub integrator: String,
+    #[serde(default = "default_dt")]
+    pub dt: f32,
+    pub colors: Vec<Color>,
+    pub friction: Vec<f32>,
+    pub mass: Vec<f32>,
+    pub radius: Vec<f32>,
+    pub attract: Vec<Vec<f32>>,
+    pub min_r: Vec<Vec<f32>>,
+    pub max_r: Vec<Vec<f32>>,
+}
+
+impl Rules {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RulesError> {
+        let rules: Rules = toml::from_str(&fs::read_to_string(path)?)?;
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), RulesError> {
+        fs::write(path, self.to_toml()?)?;
+        Ok(())
+    }
+
+    /// Write the rules as TOML, putting each row of a matrix on its own line.
+    pub fn to_toml(&self) -> Result<String, RulesError> {
+        let mut value = toml::Value::try_from(self)?;
+        shorten_floats(&mut value);
+        let mut out = String::new();
+        for (key, value) in value.as_table().unwrap() {
+            match value.as_array() {
+                Some(rows) if rows.iter().all(toml::Value::is_array) => {
+                    out.push_str(&format!("{} = [\n", key));
+                    for row in rows {
+                        out.push_str(&format!("    {},\n", row));
+                    }
+                    out.push_str("]\n");
+                }
+                _ => out.push_str(&format!("{} = {}\n", key, value)),
+            }
+        }
+        Ok(out)
+    }
+
+    pub fn types(&self) -> ParticleTypes {
+        let mut types = ParticleTypes::with_len(self.colors.len());
+        for (i, &color) in self.colors.iter().enumerate() {
+            *types.color_mut(i).unwrap() = color;
+            *types.friction_mut(i).unwrap() = self.friction[i];
+            *types.mass_mut(i).unwrap() = self.mass[i];
+            *types.radius_mut(i).unwrap() = self.radius[i];
+            for j in 0..self.colors.len() {
+                *types.attract_mut(i, j).unwrap() = self.attract[i][j];
+                *types.min_r_mut(i, j).unwrap() = self.min_r[i][j];
+                *types.max_r_mut(i, j).unwrap() = self.max_r[i][j];
+            }
+        }
+        types
+    }
+
+    pub fn set_types(&mut self, types: &ParticleTypes) {
+        let range = 0..types.len();
+        let matrix = |get: fn(&ParticleTypes, usize, usize) -> Option<&f32>| {
+            range
+                .clone()
+                .map(|i| range.clone().map(|j| *get(types, i, j).unwrap()).collect())
+                .collect()
+        };
+        self.colors = range.clone().map(|i| *types.color(i).unwrap()).collect();
+        self.friction = range.clone().map(|i| *types.friction(i).unwrap()).collect();
+        self.mass = range.clone().map(|i| *types.mass(i).unwrap()).collect();
+        self.radius = range.clone().map(|i| *types.radius(i).unwrap()).collect();
+        self.attract = matrix(ParticleTypes::attract);
+        self.min_r = matrix(ParticleTypes::min_r);
+        self.max_r = matrix(ParticleTypes::max_r);
+    }
+
+    /// Check that the rules describe a world and types the universe can use.
+    pub fn validate(&self) -> Result<(), RulesError> {
+        let len = self.colors.len();
+        if len == 0 || len > u8::MAX as usize + 1 {
+            return Err(RulesError::Invalid(format!(
+                "expected between 1 and 256 particle types, found {}",
+                len
+            )));
+        }
+        if Boundary::by_name(&self.boundary).is_none() {
+            return Err(RulesError::Invalid(format!(
+                "unknown boundary '{}'",
+                self.boundary
+            )));
+        }
+        if kernel::by_name(&self.kernel).is_none() {
+            return Err(RulesError::Invalid(format!(
+                "unknown force kernel '{}'",
+                self.kernel
+            )));
+        }
+        if Integrator::by_name(&self.integrator).is_none() {
+            return Err(RulesError::Invalid(format!(
+                "unknown integrator '{}'",
+                self.integrator
+            )));
+        }
+        if !(self.dt > 0.0 && self.dt.is_finite()) {
+            return Err(RulesError::Invalid(format!(
+                "timestep must be positive, found {}",
+                self.dt
+            )));
+        }
+        let positive = |v: f32| v > 0.0 && v.is_finite();
+        if !(positive(self.width) && positive(self.height)) {
+            return Err(RulesError::Invalid(format!(
+                "world size must be positive, found {}x{}",
+                self.width, self.height
+            )));
+        }
+        for (name, values) in [
+            ("friction", &self.friction),
+            ("mass", &self.mass),
+            ("radius", &self.radius),
+        ] {
+            if values.len() != len {
+                return Err(RulesError::Invalid(format!(
+                    "'{}' must have one value per particle type",
+                    name
+                )));
+            }
+        }
+        // Velocities are damped by `(1 - friction)^dt`, which is not a real
+        // number for friction above one and speeds particles up below zero.
+        if self.friction.iter().any(|f| !(0.0..=1.0).contains(f)) {
+            return Err(RulesError::Invalid(
+                "friction must be between 0 and 1".to_string(),
+            ));
+        }
+        if self.mass.iter().any(|&m| m.is_nan() || m <= 0.0) {
+            return Err(RulesError::Invalid("mass must be positive".to_string()));
+        }
+        if self.radius.iter().any(|&r| r.is_nan() || r <= 0.0) {
+            return Err(RulesError::Invalid("radius must be positive".to_string()));
+        }
+        for (name, matrix) in [
+            ("attract", &self.attract),
+            ("min_r", &self.min_r),
+            ("max_r", &self.max_r),
+        ] {
+            if matrix.len() != len || matrix.iter().any(|row| row.len() != len) {
+                return Err(RulesError::Invalid(format!(
+                    "'{}' must be a {}x{} matrix",
+                    name, len, len
+                )));
+            }
+            if matrix.iter().flatten().any(|v| !v.is_finite()) {
+                return Err(RulesError::Invalid(format!(
+                    "'{}' must only contain finite numbers",
+                    name
+                )));
+            }
+        }
+        // The neighbour grid only searches as far as the largest `max_r`, so a
+        // negative one would leave pairs it never finds.
+        let mut ranges = self.min_r.iter().flatten().zip(self.max_r.iter().flatten());
+        if ranges.any(|(&min_r, &max_r)| !(0.0 <= min_r && min_r <= max_r)) {
+            return Err(RulesError::Invalid(
+                "every min_r must be between 0 and its max_r".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn default_integrator() -> String {
+    Integrator::default().name().to_string()
+}
+
+fn default_dt() -> f32 {
+    1.0
+}
+
+/// Replace every float with the shortest decimal that reads back as the same
+/// `f32`, so `0.05` is not written as `0.05000000074505806`.
+fn shorten_floats(value: &mut toml::Value) {
+    match value {
+        toml::Value::Float(f) => *f = (*f as f32).to_string().parse().unwrap(),
+        toml::Value::Array(values) => values.iter_mut().for_each(shorten_floats),
+        toml::Value::Table(table) => table.iter_mut().for_each(|(_, v)| shorten_floats(v)),
+        _ => (),
+    }
+}
+
+#[derive(Debug)]
+pub enum RulesError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    Write(toml::ser::Error),
+    Invalid(String),
+}
+
+impl Display for RulesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RulesError::Io(e) => write!(f, "{}", e),
+            RulesError::Parse(e) => write!(f, "could not parse rules: {}", e),
+            RulesError::Write(e) => write!(f, "could not write rules: {}", e),
+            RulesError::Invalid(e) => write!(f, "invalid rules: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RulesError {}
+
+impl From<io::Error> for RulesError {
+    fn from(e: io::Error) -> Self {
+        RulesError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for RulesError {
+    fn from(e: toml::de::Error) -> Self {
+        RulesError::Parse(e)
+    }
+}
+
+impl From<toml::ser::Error> for RulesError {
+    fn from(e: toml::ser::Error) -> Self {
+        RulesError::Write(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{preset::PRESETS, universe::Universe};
+
+    fn rules() -> Rules {
+        let mut universe = Universe::new(0, 0, 600.0, 400.0, 0);
+        universe.apply_preset(PRESETS[0], 1);
+        universe.rules()
+    }
+
+    fn assert_invalid(rules: &Rules) {
+        assert!(
+            matches!(rules.validate(), Err(RulesError::Invalid(_))),
+            "accepted {:?}",
+            rules
+        );
+    }
+
+    #[test]
+    fn saved_rules_load_exactly() {
+        let path =
+            std::env::temp_dir().join(format!("particle_life_{}_rules.toml", std::process::id()));
+        for (i, preset) in PRESETS.iter().enumerate() {
+            let mut universe = Universe::new(0, 0, 600.0, 400.0, 0);
+            universe.apply_preset(preset, i as u64);
+            universe.set_dt(0.1);
+            let rules = universe.rules();
+            rules.save(&path).unwrap();
+            let loaded = Rules::load(&path).unwrap();
+            let bits = |rules: &Rules| {
+                rules
+                    .friction
+                    .iter()
+                    .chain(&rules.mass)
+                    .chain(&rules.radius)
+                    .chain(rules.attract.iter().flatten())
+                    .chain(rules.min_r.iter().flatten())
+                    .chain(rules.max_r.iter().flatten())
+                    .chain([&rules.width, &rules.height, &rules.dt])
+                    .map(|v| v.to_bits())
+                    .collect::<Vec<_>>()
+            };
+            assert!(bits(&loaded) == bits(&rules), "{} changed", preset.name);
+            assert_eq!(loaded, rules);
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn accepts_generated_rules() {
+        rules().validate().unwrap();
+    }
+
+    #[test]
+    fn rejects_bad_matrices() {
+        let mut bad = rules();
+        bad.attract.pop();
+        assert_invalid(&bad);
+
+        let mut bad = rules();
+        bad.max_r[1].push(10.0);
+        assert_invalid(&bad);
+
+        for value in [f32::NAN, f32::INFINITY] {
+            let mut bad = rules();
+            bad.attract[0][1] = value;
+            assert_invalid(&bad);
+        }
+
+        let mut bad = rules();
+        bad.max_r[0][1] = -1.0;
+        assert_invalid(&bad);
+
+        let mut bad = rules();
+        bad.min_r[1][0] = -1.0;
+        assert_invalid(&bad);
+
+        let mut bad = rules();
+        bad.min_r[1][1] = bad.max_r[1][1] + 1.0;
+        assert_invalid(&bad);
+    }
+
+    #[test]
+    fn rejects_bad_values() {
+        let mut bad = rules();
+        bad.friction[0] = 1.5;
+        assert_invalid(&bad);
+
+        let mut bad = rules();
+        bad.mass[0] = 0.0;
+        assert_invalid(&bad);
+
+        let mut bad = rules();
+        bad.radius.pop();
+        assert_invalid(&bad);
+
+        let mut bad = rules();
+        bad.width = f32::INFINITY;
+        assert_invalid(&bad);
+
+        let mut bad = rules();
+        bad.dt = 0.0;
+        assert_invalid(&bad);
+
+        let mut bad = rules();
+        bad.kernel = "nonsense".to_string();
+        assert_invalid(&bad);
+    }
+}
@@ -0,0 +1,208 @@
+use std::fmt::Display;
+
+use bincode::Options;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    rules::{Rules, RulesError},
+    universe::Universe,
+};
+
+const PREFIX: &str = "PL";
+const VERSION: u32 = 2;
+const COMPRESSION_LEVEL: u8 = 9;
+/// The most particles a code may ask for, so a bad code cannot exhaust memory.
+pub const MAX_PARTICLES: u32 = 1_000_000;
+
+/// Everything needed to recreate a world from scratch: its rules, how many
+/// particles it has and the state of the random number generator they were
+/// scattered with, so they start in exactly the same places.
+///
+/// Codes look like `PL2-...`, where the number is the version and the rest is
+/// the world compressed and written in URL-safe base64, so they can be pasted
+/// into chats, issues and links.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorldCode {
+    pub rules: Rules,
+    pub num_particles: u32,
+    /// The seed the world was generated from, for reference.
+    pub seed: u64,
+    pub(crate) rng: ChaCha8Rng,
+}
+
+impl WorldCode {
+    pub fn new(universe: &Universe) -> Self {
+        WorldCode {
+            rules: universe.rules(),
+            num_particles: universe.num_particles() as u32,
+            seed: universe.seed(),
+            rng: universe.scatter_rng().clone(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        let bytes = bincode::DefaultOptions::new().serialize(self).unwrap();
+        let bytes = miniz_oxide::deflate::compress_to_vec(&bytes, COMPRESSION_LEVEL);
+        format!(
+            "{}{}-{}",
+            PREFIX,
+            VERSION,
+            base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+        )
+    }
+
+    /// Read a code, ignoring any whitespace around or inside it.
+    pub fn decode(code: &str) -> Result<Self, WorldCodeError> {
+        let code: String = code.split_whitespace().collect();
+        let (version, data) = code
+            .strip_prefix(PREFIX)
+            .and_then(|rest| rest.split_once('-'))
+            .ok_or(WorldCodeError::Invalid("not a world code"))?;
+        if version.parse() != Ok(VERSION) {
+            return Err(WorldCodeError::Version(version.to_string()));
+        }
+        let bytes = base64::decode_config(data, base64::URL_SAFE_NO_PAD)
+            .ok()
+            .and_then(|bytes| miniz_oxide::inflate::decompress_to_vec(&bytes).ok())
+            .ok_or(WorldCodeError::Invalid("the code is damaged"))?;
+        let world: WorldCode = bincode::DefaultOptions::new()
+            .deserialize(&bytes)
+            .map_err(|_| WorldCodeError::Invalid("the code is damaged"))?;
+        if world.num_particles > MAX_PARTICLES {
+            return Err(WorldCodeError::Invalid("too many particles"));
+        }
+        world.rules.validate()?;
+        Ok(world)
+    }
+}
+
+impl Display for WorldCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.encode())
+    }
+}
+
+#[derive(Debug)]
+pub enum WorldCodeError {
+    Invalid(&'static str),
+    Version(String),
+    Rules(RulesError),
+}
+
+impl Display for WorldCodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WorldCodeError::Invalid(e) => write!(f, "invalid world code: {}", e),
+            WorldCodeError::Version(v) => write!(f, "unsupported world code version '{}'", v),
+            WorldCodeError::Rules(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for WorldCodeError {}
+
+impl From<RulesError> for WorldCodeError {
+    fn from(e: RulesError) -> Self {
+        WorldCodeError::Rules(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preset::MIXED_SIZES;
+
+    fn seeded() -> Universe {
+        let mut universe = Universe::new(0, 0, 600.0, 400.0, 0);
+        universe.apply_preset(&MIXED_SIZES, 3);
+        universe
+    }
+
+    fn bits(universe: &Universe) -> Vec<[u32; 5]> {
+        universe
+            .particles()
+            .iter()
+            .map(|p| {
+                let [x, y, vx, vy] = [p.x, p.y, p.vx, p.vy].map(f32::to_bits);
+                [x, y, vx, vy, p.p_type as u32]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn decodes_what_it_encodes() {
+        let world = WorldCode::new(&seeded());
+        let code = world.encode();
+        assert!(code.starts_with("PL2-"));
+        assert_eq!(WorldCode::decode(&code).unwrap(), world);
+
+        // Whitespace from wrapping a pasted code is ignored.
+        let (start, end) = code.split_at(code.len() / 2);
+        let wrapped = format!("  {}\n {} \n", start, end);
+        assert_eq!(WorldCode::decode(&wrapped).unwrap(), world);
+    }
+
+    #[test]
+    fn reproduces_the_starting_particles() {
+        let mut universe = seeded();
+        let start = bits(&universe);
+        for _ in 0..10 {
+            universe.step();
+        }
+        let code = WorldCode::new(&universe).encode();
+        let loaded = Universe::from_world_code(&WorldCode::decode(&code).unwrap()).unwrap();
+        assert!(bits(&loaded) == start);
+        assert_eq!(loaded.rules(), universe.rules());
+
+        // After scattering the particles again, a code starts from there.
+        universe.set_random_particles();
+        let start = bits(&universe);
+        let code = WorldCode::new(&universe).encode();
+        let loaded = Universe::from_world_code(&WorldCode::decode(&code).unwrap()).unwrap();
+        assert!(bits(&loaded) == start);
+    }
+
+    #[test]
+    fn rejects_damaged_codes() {
+        let code = WorldCode::new(&seeded()).encode();
+        for damaged in [
+            "",
+            "hello",
+            "PL2-",
+            "PL2-!!!!",
+            &code[..code.len() / 2],
+            &code.replace("PL2-", "PL2-A"),
+        ] {
+            assert!(
+                matches!(WorldCode::decode(damaged), Err(WorldCodeError::Invalid(_))),
+                "accepted '{}'",
+                damaged
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let code = WorldCode::new(&seeded()).encode();
+        for version in ["PL1-", "PL3-", "PLx-"] {
+            let code = code.replace("PL2-", version);
+            assert!(matches!(
+                WorldCode::decode(&code),
+                Err(WorldCodeError::Version(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn rejects_too_many_particles() {
+        let mut world = WorldCode::new(&seeded());
+        world.num_particles = MAX_PARTICLES;
+        assert!(WorldCode::decode(&world.encode()).is_ok());
+        world.num_particles = u32::MAX;
+        assert!(matches!(
+            WorldCode::decode(&world.encode()),
+            Err(WorldCodeError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,268 @@
+use std::{fmt::Write, fs, io, path::Path};
+
+use crate::{
+    boundary::Boundary,
+    canvas::Canvas,
+    color::Color,
+    coloring::{scale_color, Coloring, Legend},
+    universe::Universe,
+};
+
+const LEGEND_LEFT: f32 = 10.0;
+const LEGEND_BOTTOM: f32 = 10.0;
+const LEGEND_SWATCH: f32 = 6.0;
+const LEGEND_SPACING: f32 = 40.0;
+const BAR_WIDTH: f32 = 200.0;
+const BAR_HEIGHT: f32 = 12.0;
+const FONT_SIZE: f32 = 10.0;
+const TEXT: Color = Color::new(200, 200, 200, 255);
+const BOUNDARY: Color = Color::new(255, 255, 255, 64);
+
+/// What to include in an SVG of the universe besides the particles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SvgOptions {
+    /// Fill the view with this colour, or leave it transparent.
+    pub background: Option<Color>,
+    /// Outline the edges of the world.
+    pub boundary: bool,
+    /// Explain the colours in the bottom left corner.
+    pub legend: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            background: Some(Color::BLACK),
+            boundary: false,
+            legend: false,
+        }
+    }
+}
+
+/// A vector image the universe can be drawn onto, as crisp circles that can
+/// be scaled to any size.
+pub struct Svg {
+    width: f32,
+    height: f32,
+    body: String,
+}
+
+impl Svg {
+    pub fn new(width: f32, height: f32) -> Self {
+        Svg {
+            width,
+            height,
+            body: String::new(),
+        }
+    }
+
+    /// Draw `universe` as it is currently seen in the colours from `coloring`,
+    /// so the view follows the camera, along with whatever else `options` asks
+    /// for.
+    pub fn render(universe: &Universe, coloring: &Coloring, options: &SvgOptions) -> Self {
+        let mut svg = Svg::new(universe.screen_width(), universe.screen_height());
+        if let Some(color) = options.background {
+            svg.draw_rectangle(0.0, 0.0, svg.width, svg.height, color);
+        }
+        if options.boundary && universe.boundary() != Boundary::Disc {
+            let (left, top) = universe.to_screen(0.0, 0.0);
+            let (right, bottom) = universe.to_screen(universe.width(), universe.height());
+            svg.draw_rectangle_lines(left, top, right - left, bottom - top, BOUNDARY);
+        }
+        universe.draw(&mut svg, coloring, 1.0);
+        if options.legend {
+            svg.draw_legend(universe, coloring);
+        }
+        svg
+    }
+
+    pub fn draw_rectangle(&mut self, x: f32, y: f32, width: f32, height: f32, color: Color) {
+        writeln!(
+            self.body,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" {}/>"#,
+            x,
+            y,
+            width,
+            height,
+            paint("fill", color)
+        )
+        .unwrap();
+    }
+
+    pub fn draw_rectangle_lines(&mut self, x: f32, y: f32, width: f32, height: f32, color: Color) {
+        writeln!(
+            self.body,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="none" {}/>"#,
+            x,
+            y,
+            width,
+            height,
+            paint("stroke", color)
+        )
+        .unwrap();
+    }
+
+    /// Draw `text` with its top left corner at `(x, y)`.
+    pub fn draw_text(&mut self, text: &str, x: f32, y: f32, size: f32, color: Color) {
+        self.write_text(text, x, y, size, color, "start");
+    }
+
+    /// Draw `text` with its top edge at `y` and `anchor` (`start`, `middle`
+    /// or `end`) at `x`.
+    fn write_text(&mut self, text: &str, x: f32, y: f32, size: f32, color: Color, anchor: &str) {
+        writeln!(
+            self.body,
+            r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" dominant-baseline="hanging" text-anchor="{}" {}>{}</text>"#,
+            x,
+            y,
+            size,
+            anchor,
+            paint("fill", color),
+            escape(text)
+        )
+        .unwrap();
+    }
+
+    /// Explain the colours the universe is drawn in, like the legend in the
+    /// window but also listing the type colours.
+    fn draw_legend(&mut self, universe: &Universe, coloring: &Coloring) {
+        let top = self.height - LEGEND_BOTTOM - 2.0 * FONT_SIZE - BAR_HEIGHT - 6.0;
+        match coloring.legend {
+            Legend::Types => {
+                let types = universe.types();
+                let y = self.height - LEGEND_BOTTOM - FONT_SIZE;
+                for i in 0..types.len() {
+                    let x = LEGEND_LEFT + i as f32 * LEGEND_SPACING;
+                    self.draw_circle(
+                        x + LEGEND_SWATCH,
+                        y + 0.5 * FONT_SIZE,
+                        LEGEND_SWATCH,
+                        *types.color(i).unwrap(),
+                    );
+                    let label = format!("{}", i);
+                    self.draw_text(&label, x + 3.0 * LEGEND_SWATCH, y, FONT_SIZE, TEXT);
+                }
+            }
+            Legend::Scale { label, min, max } => {
+                self.draw_text(label, LEGEND_LEFT, top, FONT_SIZE, TEXT);
+                let bar_top = top + FONT_SIZE + 4.0;
+                writeln!(
+                    self.body,
+                    r#"<linearGradient id="scale">{}</linearGradient>"#,
+                    (0..=4)
+                        .map(|i| {
+                            let t = i as f32 / 4.0;
+                            format!(
+                                r#"<stop offset="{}" stop-color="{}"/>"#,
+                                t,
+                                hex(scale_color(t))
+                            )
+                        })
+                        .collect::<String>()
+                )
+                .unwrap();
+                writeln!(
+                    self.body,
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" fill="url(#scale)"/>"#,
+                    LEGEND_LEFT, bar_top, BAR_WIDTH, BAR_HEIGHT
+                )
+                .unwrap();
+                let label_top = bar_top + BAR_HEIGHT + 2.0;
+                self.draw_text(
+                    &format!("{:.2}", min),
+                    LEGEND_LEFT,
+                    label_top,
+                    FONT_SIZE,
+                    TEXT,
+                );
+                let max = format!("{:.2}", max);
+                let right = LEGEND_LEFT + BAR_WIDTH;
+                self.write_text(&max, right, label_top, FONT_SIZE, TEXT, "end");
+            }
+            Legend::Clusters(count) => {
+                let text = format!("{} clusters", count);
+                self.draw_text(&text, LEGEND_LEFT, top, FONT_SIZE, TEXT);
+            }
+        }
+    }
+
+    /// Whether a circle at `(x, y)` can be seen at all.
+    fn is_visible(&self, x: f32, y: f32, radius: f32) -> bool {
+        x + radius >= 0.0
+            && x - radius <= self.width
+            && y + radius >= 0.0
+            && y - radius <= self.height
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+}
+
+impl std::fmt::Display for Svg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = self.width,
+            h = self.height
+        )?;
+        write!(f, "{}", self.body)?;
+        writeln!(f, "</svg>")
+    }
+}
+
+impl Canvas for Svg {
+    fn draw_circle(&mut self, x: f32, y: f32, radius: f32, color: Color) {
+        if !self.is_visible(x, y, radius) {
+            return;
+        }
+        writeln!(
+            self.body,
+            r#"<circle cx="{}" cy="{}" r="{}" {}/>"#,
+            x,
+            y,
+            radius,
+            paint("fill", color)
+        )
+        .unwrap();
+    }
+
+    fn draw_circle_lines(&mut self, x: f32, y: f32, radius: f32, color: Color) {
+        writeln!(
+            self.body,
+            r#"<circle cx="{}" cy="{}" r="{}" fill="none" {}/>"#,
+            x,
+            y,
+            radius,
+            paint("stroke", color)
+        )
+        .unwrap();
+    }
+}
+
+/// `color` as an SVG `fill` or `stroke` attribute, with its opacity if it is
+/// not opaque.
+fn paint(attribute: &str, color: Color) -> String {
+    if color.a == 255 {
+        format!(r#"{}="{}""#, attribute, hex(color))
+    } else {
+        format!(
+            r#"{}="{}" {}-opacity="{:.3}""#,
+            attribute,
+            hex(color),
+            attribute,
+            color.a as f32 / 255.0
+        )
+    }
+}
+
+fn hex(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -0,0 +1,89 @@
+use raylib::{
+    color::Color,
+    math::{Rectangle, Vector2},
+    prelude::{
+        RaylibDraw, RaylibDrawHandle, RaylibHandle, RaylibTextureModeExt, RaylibThread,
+        RenderTexture2D,
+    },
+};
+
+use crate::{coloring::Coloring, universe::Universe};
+
+const MIN_FADE: f32 = 0.02;
+const MAX_FADE: f32 = 1.0;
+
+/// Draws the universe into a texture that is only partly cleared each frame,
+/// so particles leave streaks behind them in their own colour.
+pub struct Trails {
+    enabled: bool,
+    cleared: bool,
+    fade: f32,
+    target: RenderTexture2D,
+    width: i32,
+    height: i32,
+}
+
+impl Trails {
+    pub fn new(
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        width: i32,
+        height: i32,
+    ) -> Result<Self, String> {
+        Ok(Trails {
+            enabled: false,
+            cleared: false,
+            fade: 0.1,
+            target: rl.load_render_texture(thread, width as u32, height as u32)?,
+            width,
+            height,
+        })
+    }
+
+    /// Turn trails on or off, starting from a blank texture when turned on.
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        self.cleared = false;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// How much of the trail is faded out each frame, from almost nothing to
+    /// all of it.
+    pub fn fade(&self) -> f32 {
+        self.fade
+    }
+
+    pub fn set_fade(&mut self, fade: f32) {
+        self.fade = fade.clamp(MIN_FADE, MAX_FADE);
+    }
+
+    /// Fade the trails drawn so far, draw the universe on top of them in the
+    /// colours from `coloring` and show the result.
+    pub fn draw(
+        &mut self,
+        d: &mut RaylibDrawHandle,
+        thread: &RaylibThread,
+        universe: &Universe,
+        coloring: &Coloring,
+    ) {
+        {
+            let mut t = d.begin_texture_mode(thread, &mut self.target);
+            if !self.cleared {
+                t.clear_background(Color::BLACK);
+                self.cleared = true;
+            }
+            t.draw_rectangle(0, 0, self.width, self.height, Color::BLACK.fade(self.fade));
+            universe.draw(&mut t, coloring, 1.0);
+        }
+        // Render textures are stored upside down.
+        d.draw_texture_rec(
+            &self.target,
+            Rectangle::new(0.0, 0.0, self.width as f32, -self.height as f32),
+            Vector2::zero(),
+            Color::WHITE,
+        );
+    }
+}
@@ -0,0 +1,351 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{particle::Particle, universe::Universe};
+
+const MAGIC: &[u8; 4] = b"PLTR";
+const VERSION: u32 = 1;
+
+/// How a trajectory is written out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrajectoryFormat {
+    /// One `step,index,type,x,y,vx,vy` row per particle per recorded step.
+    Csv,
+    /// A compact little-endian binary file. It starts with `PLTR` and a `u32`
+    /// version, followed by one block per recorded step: the step as a `u64`,
+    /// the number of particles `n` as a `u32`, then `n` `u8` types and `n`
+    /// `f32`s each of x, y, vx and vy. A particle's index is its position in
+    /// the block.
+    Binary,
+}
+
+impl TrajectoryFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            TrajectoryFormat::Csv => "csv",
+            TrajectoryFormat::Binary => "binary",
+        }
+    }
+
+    /// CSV for files ending in `.csv`, and binary for anything else.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => TrajectoryFormat::Csv,
+            _ => TrajectoryFormat::Binary,
+        }
+    }
+}
+
+/// Logs the state of every particle to a file every `stride` steps.
+pub struct TrajectoryRecorder {
+    path: PathBuf,
+    out: BufWriter<File>,
+    format: TrajectoryFormat,
+    stride: u64,
+    last_step: Option<u64>,
+    frames: u64,
+}
+
+impl TrajectoryRecorder {
+    /// Start a trajectory at `path`, in the format its extension implies.
+    pub fn create(path: impl AsRef<Path>, stride: u64) -> io::Result<Self> {
+        let format = TrajectoryFormat::from_path(&path);
+        Self::create_with_format(path, format, stride)
+    }
+
+    pub fn create_with_format(
+        path: impl AsRef<Path>,
+        format: TrajectoryFormat,
+        stride: u64,
+    ) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut out = BufWriter::new(File::create(&path)?);
+        match format {
+            TrajectoryFormat::Csv => writeln!(out, "step,index,type,x,y,vx,vy")?,
+            TrajectoryFormat::Binary => {
+                out.write_all(MAGIC)?;
+                out.write_all(&VERSION.to_le_bytes())?;
+            }
+        }
+        Ok(TrajectoryRecorder {
+            path,
+            out,
+            format,
+            stride: stride.max(1),
+            last_step: None,
+            frames: 0,
+        })
+    }
+
+    /// The file the trajectory is written to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn format(&self) -> TrajectoryFormat {
+        self.format
+    }
+
+    pub fn stride(&self) -> u64 {
+        self.stride
+    }
+
+    /// The number of steps recorded so far.
+    pub fn len(&self) -> u64 {
+        self.frames
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames == 0
+    }
+
+    /// Record the universe if its step is a multiple of the stride and has
+    /// not been recorded already. Call after every step.
+    // `is_multiple_of` needs Rust 1.87, newer than this crate asks for.
+    #[allow(clippy::manual_is_multiple_of)]
+    pub fn record(&mut self, universe: &Universe) -> io::Result<()> {
+        let step = universe.step_count();
+        if step % self.stride != 0 || self.last_step == Some(step) {
+            return Ok(());
+        }
+        self.write(step, universe.particles())?;
+        self.last_step = Some(step);
+        self.frames += 1;
+        Ok(())
+    }
+
+    fn write(&mut self, step: u64, particles: &[Particle]) -> io::Result<()> {
+        let out = &mut self.out;
+        match self.format {
+            TrajectoryFormat::Csv => {
+                for (i, p) in particles.iter().enumerate() {
+                    writeln!(
+                        out,
+                        "{},{},{},{},{},{},{}",
+                        step, i, p.p_type, p.x, p.y, p.vx, p.vy
+                    )?;
+                }
+            }
+            TrajectoryFormat::Binary => {
+                out.write_all(&step.to_le_bytes())?;
+                out.write_all(&(particles.len() as u32).to_le_bytes())?;
+                let types: Vec<u8> = particles.iter().map(|p| p.p_type).collect();
+                out.write_all(&types)?;
+                for column in [
+                    |p: &Particle| p.x,
+                    |p: &Particle| p.y,
+                    |p: &Particle| p.vx,
+                    |p: &Particle| p.vy,
+                ] {
+                    for p in particles {
+                        out.write_all(&column(p).to_le_bytes())?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write out anything still buffered.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// Every particle at one recorded step of a binary trajectory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrajectoryFrame {
+    pub step: u64,
+    pub particles: Vec<Particle>,
+}
+
+/// Reads the steps back out of a binary trajectory.
+pub struct TrajectoryReader {
+    reader: BufReader<File>,
+    /// The bytes left to read, so a damaged particle count is caught before
+    /// room is made for that many particles.
+    remaining: u64,
+}
+
+impl TrajectoryReader {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let size = file.metadata()?.len();
+        let mut reader = BufReader::new(file);
+        let mut header = [0; 8];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(invalid("not a binary trajectory file".to_string()));
+        }
+        let version = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        if version != VERSION {
+            return Err(invalid(format!(
+                "unsupported trajectory version {}",
+                version
+            )));
+        }
+        Ok(TrajectoryReader {
+            reader,
+            remaining: size - header.len() as u64,
+        })
+    }
+
+    /// The next recorded step, or `None` at the end of the file.
+    pub fn next_frame(&mut self) -> io::Result<Option<TrajectoryFrame>> {
+        let mut head = [0; 12];
+        match self.reader.read_exact(&mut head[..1]) {
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            result => result?,
+        }
+        self.reader.read_exact(&mut head[1..])?;
+        let mut step = [0; 8];
+        step.copy_from_slice(&head[..8]);
+        let len = u32::from_le_bytes([head[8], head[9], head[10], head[11]]) as usize;
+        // A type and four `f32`s for each particle.
+        let size = 17 * len as u64;
+        self.remaining = self.remaining.saturating_sub(head.len() as u64);
+        if size > self.remaining {
+            return Err(invalid(format!(
+                "step {} has {} particles but the file ends first",
+                u64::from_le_bytes(step),
+                len
+            )));
+        }
+        self.remaining -= size;
+
+        let mut types = vec![0; len];
+        self.reader.read_exact(&mut types)?;
+        let mut columns = vec![0; 16 * len];
+        self.reader.read_exact(&mut columns)?;
+        let value = |column: usize, i: usize| {
+            let start = (column * len + i) * 4;
+            let mut bytes = [0; 4];
+            bytes.copy_from_slice(&columns[start..start + 4]);
+            f32::from_le_bytes(bytes)
+        };
+        let particles = (0..len)
+            .map(|i| Particle {
+                x: value(0, i),
+                y: value(1, i),
+                vx: value(2, i),
+                vy: value(3, i),
+                p_type: types[i],
+            })
+            .collect();
+        Ok(Some(TrajectoryFrame {
+            step: u64::from_le_bytes(step),
+            particles,
+        }))
+    }
+}
+
+impl Iterator for TrajectoryReader {
+    type Item = io::Result<TrajectoryFrame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_frame().transpose()
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::*;
+    use crate::preset::BALANCED;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("particle_life_{}_{}", std::process::id(), name))
+    }
+
+    /// Record every `stride` steps of a small universe, returning what was
+    /// recorded.
+    fn record(path: &Path, stride: u64) -> Vec<TrajectoryFrame> {
+        let mut universe = Universe::new(0, 0, 400.0, 300.0, 0);
+        universe.set_population(3, 50);
+        universe.re_seed(&BALANCED, 2);
+        let mut recorder = TrajectoryRecorder::create(path, stride).unwrap();
+        let mut frames = Vec::new();
+        for _ in 0..=10 {
+            let recorded = recorder.len();
+            recorder.record(&universe).unwrap();
+            // Recording the same step twice only writes it once.
+            recorder.record(&universe).unwrap();
+            if recorder.len() > recorded {
+                frames.push(TrajectoryFrame {
+                    step: universe.step_count(),
+                    particles: universe.particles().to_vec(),
+                });
+            }
+            universe.step();
+        }
+        recorder.flush().unwrap();
+        frames
+    }
+
+    #[test]
+    fn reads_back_what_was_recorded() {
+        let path = temp_path("trajectory.bin");
+        let frames = record(&path, 3);
+        let read = TrajectoryReader::open(&path)
+            .unwrap()
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            read.iter().map(|f| f.step).collect::<Vec<_>>(),
+            [0, 3, 6, 9]
+        );
+        assert_eq!(read, frames);
+    }
+
+    #[test]
+    fn writes_csv_rows() {
+        let path = temp_path("trajectory.csv");
+        let frames = record(&path, 5);
+        let csv = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("step,index,type,x,y,vx,vy"));
+        assert_eq!(lines.count(), frames.len() * 50);
+    }
+
+    #[test]
+    fn rejects_truncated_files() {
+        let path = temp_path("truncated.bin");
+        record(&path, 10);
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        let mut reader = TrajectoryReader::open(&path).unwrap();
+        assert!(reader.next_frame().unwrap().is_some());
+        let error = reader.next_frame().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // A particle count larger than the file is refused before reading.
+        let mut bytes = bytes;
+        bytes[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+        fs::write(&path, &bytes).unwrap();
+        let error = TrajectoryReader::open(&path)
+            .unwrap()
+            .next_frame()
+            .unwrap_err();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        let path = temp_path("other.bin");
+        fs::write(&path, b"PLSS\x01\0\0\0").unwrap();
+        let result = TrajectoryReader::open(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -1,74 +1,275 @@
 use std::{fmt::Display, vec};
 
-use rand::{distributions::Distribution, prelude::ThreadRng, Rng};
-use raylib::{
-    color::Color,
-    math::Vector2,
-    prelude::{RaylibDraw, RaylibDrawHandle},
-};
+use rand::{distributions::Distribution, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use statrs::distribution::{Normal, Uniform};
 
 use crate::{
+    boundary::Boundary,
     camera::Camera,
-    max, min,
-    particle::{Particle, ParticleTypes},
+    canvas::Canvas,
+    color::Color,
+    coloring::Coloring,
+    grid::Grid,
+    integrator::Integrator,
+    kernel::{self, ForceKernel, PairRule, Tent},
+    math::Vector2,
+    particle::{Particle, ParticleTypes, DEFAULT_RADIUS},
+    preset::Preset,
+    rules::{Rules, RulesError},
+    share::WorldCode,
+    snapshot::Snapshot,
 };
 
-const RADIUS: f32 = 5.0;
-const DIAMETER: f32 = 2.0 * RADIUS;
-const R_SMOOTH: f32 = 2.0;
+const MIN_UNBOUNDED_ZOOM: f32 = 0.2;
+
+/// Keep a view of length `view` centred on `centre` inside a world of length
+/// `size`, or centre it if the whole world fits in the view.
+fn clamp_centre(centre: f32, size: f32, view: f32) -> f32 {
+    if view >= size {
+        size * 0.5
+    } else {
+        centre.max(view * 0.5).min(size - view * 0.5)
+    }
+}
+
+/// The shortest signed distance equivalent to `d` on a loop of length `size`.
+fn min_image(d: f32, size: f32) -> f32 {
+    d - size * (d / size).round()
+}
 
 pub struct Universe {
     centre: Vector2,
     dimentions: Vector2,
+    screen: Vector2,
     zoom: f32,
-    wrap: bool,
+    unbounded_view: bool,
+    boundary: Boundary,
     particles: Vec<Particle>,
+    num_particles: usize,
     types: ParticleTypes,
-    rng: ThreadRng,
+    rng: ChaCha8Rng,
+    /// The random number generator as it was when the particles were last
+    /// scattered, so a world code can scatter them the same way again.
+    scatter_rng: ChaCha8Rng,
+    seed: u64,
+    step_count: u64,
     rand_settings: RandomSettings,
-    friction: f32,
-    flat_force: bool,
+    kernel: &'static dyn ForceKernel,
+    integrator: Integrator,
+    dt: f32,
+    grid: Grid,
 }
 
 impl Universe {
-    pub fn new(num_types: usize, num_particles: usize, width: f32, height: f32) -> Self {
+    pub fn new(num_types: usize, num_particles: usize, width: f32, height: f32, seed: u64) -> Self {
         Universe {
             centre: Vector2::new(width * 0.5, height * 0.5),
             dimentions: Vector2::new(width, height),
+            screen: Vector2::new(width, height),
             zoom: 1.0,
-            wrap: true,
+            unbounded_view: false,
+            boundary: Boundary::default(),
             types: ParticleTypes::with_len(num_types),
             particles: vec![Particle::default(); num_particles],
-            rng: rand::thread_rng(),
+            num_particles,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            scatter_rng: ChaCha8Rng::seed_from_u64(seed),
+            seed,
+            step_count: 0,
             rand_settings: RandomSettings::new(),
-            friction: 0.0,
-            flat_force: false,
+            kernel: &Tent,
+            integrator: Integrator::default(),
+            dt: 1.0,
+            grid: Grid::new(),
         }
     }
 
     pub fn set_population(&mut self, num_types: usize, num_particles: usize) {
         self.types.resize(num_types);
         self.particles.resize(num_particles, Particle::default());
+        self.num_particles = num_particles;
     }
 
-    pub fn re_seed(
-        &mut self,
-        attract_mean: f32,
-        attract_std: f32,
-        min_r: (f32, f32),
-        max_r: (f32, f32),
-        friction: f32,
-        flat_force: bool,
-    ) {
-        self.friction = friction;
-        self.flat_force = flat_force;
-        self.rand_settings
-            .re_seed(attract_mean, attract_std, min_r, max_r);
+    /// Re-seed the rules and particles from `seed`, using the random settings
+    /// and physics of `preset` but keeping the current population.
+    pub fn re_seed(&mut self, preset: &Preset, seed: u64) {
+        self.seed = seed;
+        self.step_count = 0;
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self.kernel = preset.kernel;
+        self.rand_settings = RandomSettings::from_preset(preset);
         self.set_random_types();
         self.set_random_particles();
     }
 
+    /// Resize the population to match `preset` and re-seed using its rules.
+    pub fn apply_preset(&mut self, preset: &Preset, seed: u64) {
+        self.set_population(preset.num_types, preset.num_particles);
+        self.re_seed(preset, seed);
+    }
+
+    pub fn rules(&self) -> Rules {
+        let mut rules = Rules {
+            width: self.dimentions.x,
+            height: self.dimentions.y,
+            boundary: self.boundary.name().to_string(),
+            kernel: self.kernel.name().to_string(),
+            integrator: self.integrator.name().to_string(),
+            dt: self.dt,
+            colors: Vec::new(),
+            friction: Vec::new(),
+            mass: Vec::new(),
+            radius: Vec::new(),
+            attract: Vec::new(),
+            min_r: Vec::new(),
+            max_r: Vec::new(),
+        };
+        rules.set_types(&self.types);
+        rules
+    }
+
+    /// Replace the rules, keeping the particles where they are relative to the
+    /// size of the world.
+    pub fn set_rules(&mut self, rules: &Rules) -> Result<(), RulesError> {
+        rules.validate()?;
+        let (sx, sy) = (
+            rules.width / self.dimentions.x,
+            rules.height / self.dimentions.y,
+        );
+        for p in self.particles.iter_mut() {
+            p.x *= sx;
+            p.y *= sy;
+            p.p_type = (p.p_type as usize % rules.colors.len()) as u8;
+        }
+        self.centre.x *= sx;
+        self.centre.y *= sy;
+        self.load_rules(rules);
+        Ok(())
+    }
+
+    fn load_rules(&mut self, rules: &Rules) {
+        self.types = rules.types();
+        self.dimentions = Vector2::new(rules.width, rules.height);
+        self.boundary = Boundary::by_name(&rules.boundary).unwrap();
+        self.kernel = kernel::by_name(&rules.kernel).unwrap();
+        self.integrator = Integrator::by_name(&rules.integrator).unwrap();
+        self.dt = rules.dt;
+    }
+
+    /// Capture everything needed to continue this simulation exactly.
+    pub fn snapshot(&self, camera: Option<&Camera>) -> Snapshot {
+        Snapshot {
+            rules: self.rules(),
+            particles: self.particles.clone(),
+            rand_settings: self.rand_settings.clone(),
+            rng: self.rng.clone(),
+            scatter_rng: self.scatter_rng.clone(),
+            seed: self.seed,
+            step_count: self.step_count,
+            centre: self.centre,
+            zoom: self.zoom,
+            camera: camera.cloned(),
+        }
+    }
+
+    pub fn from_snapshot(snapshot: &Snapshot) -> Result<Self, RulesError> {
+        let rules = &snapshot.rules;
+        let mut universe = Universe::new(0, 0, rules.width, rules.height, snapshot.seed);
+        universe.restore(snapshot)?;
+        Ok(universe)
+    }
+
+    /// Continue from a snapshot, discarding the current state.
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<(), RulesError> {
+        snapshot.rules.validate()?;
+        self.load_rules(&snapshot.rules);
+        self.particles = snapshot.particles.clone();
+        self.num_particles = self.particles.len();
+        self.rand_settings = snapshot.rand_settings.clone();
+        self.rng = snapshot.rng.clone();
+        self.scatter_rng = snapshot.scatter_rng.clone();
+        self.seed = snapshot.seed;
+        self.step_count = snapshot.step_count;
+        self.centre = snapshot.centre;
+        self.zoom = snapshot.zoom;
+        Ok(())
+    }
+
+    pub fn from_world_code(world: &WorldCode) -> Result<Self, RulesError> {
+        let rules = &world.rules;
+        let mut universe = Universe::new(0, 0, rules.width, rules.height, world.seed);
+        universe.load_world_code(world)?;
+        Ok(universe)
+    }
+
+    /// Start again from a shared world, scattering its particles exactly as
+    /// they were when the world the code came from was last seeded.
+    pub fn load_world_code(&mut self, world: &WorldCode) -> Result<(), RulesError> {
+        world.rules.validate()?;
+        self.set_population(world.rules.colors.len(), world.num_particles as usize);
+        self.load_rules(&world.rules);
+        self.seed = world.seed;
+        self.step_count = 0;
+        self.rng = world.rng.clone();
+        self.set_random_particles();
+        self.centre = Vector2::new(0.5 * world.rules.width, 0.5 * world.rules.height);
+        Ok(())
+    }
+
+    /// The number of steps run since the universe was last seeded.
+    pub fn step_count(&self) -> u64 {
+        self.step_count
+    }
+
+    pub fn kernel(&self) -> &'static dyn ForceKernel {
+        self.kernel
+    }
+
+    /// Use `kernel` for the forces, registering it if it is not one of the
+    /// built-in kernels so the rules can be saved and loaded again.
+    pub fn set_kernel(&mut self, kernel: &'static dyn ForceKernel) {
+        kernel::register(kernel);
+        self.kernel = kernel;
+    }
+
+    pub fn integrator(&self) -> Integrator {
+        self.integrator
+    }
+
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        self.integrator = integrator;
+    }
+
+    /// The length of time each call to `step` advances the simulation by.
+    pub fn dt(&self) -> f32 {
+        self.dt
+    }
+
+    /// Set the timestep, ignoring values that are not positive.
+    pub fn set_dt(&mut self, dt: f32) {
+        if dt > 0.0 && dt.is_finite() {
+            self.dt = dt;
+        }
+    }
+
+    /// The seed the current rules and starting particles were generated from.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The number of particles the universe is scattered with.
+    pub fn num_particles(&self) -> usize {
+        self.num_particles
+    }
+
+    pub(crate) fn scatter_rng(&self) -> &ChaCha8Rng {
+        &self.scatter_rng
+    }
+
     pub fn set_random_types(&mut self) {
         let settings = &self.rand_settings;
         let rand_attr =
@@ -77,19 +278,34 @@ impl Universe {
             Uniform::new(settings.min_r_lower as f64, settings.min_r_upper as f64).unwrap();
         let rand_max_r =
             Uniform::new(settings.max_r_lower as f64, settings.max_r_upper as f64).unwrap();
+        let rand_friction = Uniform::new(
+            settings.friction_lower as f64,
+            settings.friction_upper as f64,
+        )
+        .unwrap();
+        let rand_mass =
+            Uniform::new(settings.mass_lower as f64, settings.mass_upper as f64).unwrap();
+        let rand_radius =
+            Uniform::new(settings.radius_lower as f64, settings.radius_upper as f64).unwrap();
         let len = self.types.len() as f32;
         for i in 0..self.types.len() {
             *self.types.color_mut(i).unwrap() =
                 Color::new(((i as f32 / len) * 255.0) as u8, 255, self.rng.gen(), 255);
+            *self.types.friction_mut(i).unwrap() = rand_friction.sample(&mut self.rng) as f32;
+            *self.types.mass_mut(i).unwrap() = rand_mass.sample(&mut self.rng) as f32;
+            *self.types.radius_mut(i).unwrap() = rand_radius.sample(&mut self.rng) as f32;
+        }
+        for i in 0..self.types.len() {
             for j in 0..self.types.len() {
+                let contact = self.types.contact_distance(i, j);
                 if i == j {
                     *self.types.attract_mut(i, j).unwrap() =
                         -(rand_attr.sample(&mut self.rng).abs() as f32);
-                    *self.types.min_r_mut(i, j).unwrap() = DIAMETER;
+                    *self.types.min_r_mut(i, j).unwrap() = contact;
                 } else {
                     *self.types.attract_mut(i, j).unwrap() = rand_attr.sample(&mut self.rng) as f32;
                     *self.types.min_r_mut(i, j).unwrap() =
-                        DIAMETER.max(rand_min_r.sample(&mut self.rng) as f32);
+                        contact.max(rand_min_r.sample(&mut self.rng) as f32);
                 }
                 *self.types.max_r_mut(i, j).unwrap() =
                     (rand_max_r.sample(&mut self.rng) as f32).max(*self.types.min_r(i, j).unwrap());
@@ -99,7 +315,12 @@ impl Universe {
         }
     }
 
+    /// Scatter the original number of particles, including any that have been
+    /// absorbed, at random around the middle of the world.
     pub fn set_random_particles(&mut self) {
+        self.scatter_rng = self.rng.clone();
+        self.particles
+            .resize(self.num_particles, Particle::default());
         let rand_norm = Normal::new(0.0, 1.0).unwrap();
         for p in self.particles.iter_mut() {
             p.p_type = self.rng.gen_range(0..self.types.len()) as u8;
@@ -111,129 +332,318 @@ impl Universe {
         }
     }
 
+    /// Switch between wrapping around and bouncing off the edges of the world.
     pub fn toggle_wrap(&mut self) {
-        self.wrap = !self.wrap;
+        self.boundary = if self.boundary.wraps() {
+            Boundary::Reflective
+        } else {
+            Boundary::Periodic
+        };
     }
 
-    pub fn step(&mut self) {
-        let len = self.particles.len();
-        for i in 0..len {
-            let p = *self.particles.get(i).unwrap();
-            for j in 0..len {
-                let q = *self.particles.get(j).unwrap();
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
 
-                let (mut dx, mut dy) = (q.x - p.x, q.y - p.y);
-                if self.wrap {
-                    if dx > self.dimentions.x * 0.5 {
-                        dx -= self.dimentions.x;
-                    } else if dx < -self.dimentions.x {
-                        dx += self.dimentions.x;
-                    }
-                    if dy > self.dimentions.y * 0.5 {
-                        dy -= self.dimentions.y;
-                    } else if dy < -self.dimentions.y {
-                        dy += self.dimentions.y;
-                    }
-                }
-                let r2 = dx * dx + dy * dy;
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
 
-                let min_r = *self
-                    .types
-                    .min_r(p.p_type as usize, q.p_type as usize)
-                    .unwrap();
-                let max_r = *self
-                    .types
-                    .max_r(p.p_type as usize, q.p_type as usize)
-                    .unwrap();
-                if r2 > max_r * max_r || r2 < 0.01 {
-                    continue;
+    /// Advance the simulation by one timestep of length `dt` using the current
+    /// integrator.
+    ///
+    /// Forces are always computed from an unchanged view of the particles, so the
+    /// result does not depend on the order particles are visited in. With the
+    /// `parallel` feature enabled the work is spread over threads and produces
+    /// bit-identical trajectories.
+    ///
+    /// Returns how many particles left the world and were removed, after which
+    /// the indices of the particles behind them have moved down.
+    pub fn step(&mut self) -> usize {
+        self.step_count += 1;
+        let dt = self.dt;
+        match self.integrator {
+            Integrator::SemiImplicitEuler => {
+                let velocities = self.velocities(None, dt);
+                for (p, (vx, vy)) in self.particles.iter_mut().zip(velocities) {
+                    p.vx = vx;
+                    p.vy = vy;
+                    p.x += vx * dt;
+                    p.y += vy * dt;
                 }
-                let r = r2.sqrt();
-                dx /= r;
-                dy /= r;
-                let f = if r > min_r {
-                    if self.flat_force {
-                        *self
-                            .types
-                            .attract(p.p_type as usize, q.p_type as usize)
-                            .unwrap()
-                    } else {
-                        let numer = 2.0 * (r - 0.5 * (max_r + min_r)).abs();
-                        let denom = max_r - min_r;
-                        *self
-                            .types
-                            .attract(p.p_type as usize, q.p_type as usize)
-                            .unwrap()
-                            * (1.0 - (numer / denom))
-                    }
-                } else {
-                    R_SMOOTH * min_r * (1.0 / (min_r + R_SMOOTH) - 1.0 / (r + R_SMOOTH))
-                };
-
-                let p_mut = self.particles.get_mut(i).unwrap();
-                p_mut.vx += f * dx;
-                p_mut.vy += f * dy;
             }
-        }
-
-        for p in self.particles.iter_mut() {
-            p.x += p.vx;
-            p.y += p.vy;
-            p.vx *= 1.0 - self.friction;
-            p.vy *= 1.0 - self.friction;
-            if self.wrap {
-                if p.x < 0.0 {
-                    p.x += self.dimentions.x;
-                } else if p.x >= self.dimentions.x {
-                    p.x -= self.dimentions.x;
-                }
-                if p.y < 0.0 {
-                    p.y += self.dimentions.y;
-                } else if p.y >= self.dimentions.y {
-                    p.y -= self.dimentions.y;
+            Integrator::VelocityVerlet => {
+                let mut stage = self.particles.clone();
+                let a0 = self.accelerations(&mut stage);
+                for ((s, p), (ax, ay)) in stage.iter_mut().zip(&self.particles).zip(&a0) {
+                    s.x = p.x + p.vx * dt + 0.5 * ax * dt * dt;
+                    s.y = p.y + p.vy * dt + 0.5 * ay * dt * dt;
+                    self.wrap_particle(s);
                 }
-            } else {
-                if p.x <= DIAMETER {
-                    p.vx = -p.vx;
-                    p.x = DIAMETER;
-                } else if p.x >= self.dimentions.x - DIAMETER {
-                    p.vx = -p.vx;
-                    p.x = self.dimentions.x - DIAMETER;
+                let a1 = self.accelerations(&mut stage);
+                for (((p, s), (ax0, ay0)), (ax1, ay1)) in
+                    self.particles.iter_mut().zip(&stage).zip(a0).zip(a1)
+                {
+                    p.x = s.x;
+                    p.y = s.y;
+                    p.vx += 0.5 * (ax0 + ax1) * dt;
+                    p.vy += 0.5 * (ay0 + ay1) * dt;
                 }
-                if p.y <= DIAMETER {
-                    p.vy = -p.vy;
-                    p.y = DIAMETER;
-                } else if p.y >= self.dimentions.y - DIAMETER {
-                    p.vy = -p.vy;
-                    p.y = self.dimentions.y - DIAMETER;
+            }
+            Integrator::Rk4 => {
+                let mut stage = self.particles.clone();
+                let a1 = self.accelerations(&mut stage);
+                self.rk4_stage(&mut stage, &[], 0.0, 0.5 * dt);
+                let a2 = self.accelerations(&mut stage);
+                self.rk4_stage(&mut stage, &a1, 0.5 * dt, 0.5 * dt);
+                let a3 = self.accelerations(&mut stage);
+                self.rk4_stage(&mut stage, &a2, 0.5 * dt, dt);
+                let a4 = self.accelerations(&mut stage);
+                for (i, p) in self.particles.iter_mut().enumerate() {
+                    let (vx, vy) = (p.vx, p.vy);
+                    // The stage velocities are v, v + a1 dt/2, v + a2 dt/2 and v + a3 dt.
+                    p.x += dt / 6.0 * (6.0 * vx + (a1[i].0 + a2[i].0 + a3[i].0) * dt);
+                    p.y += dt / 6.0 * (6.0 * vy + (a1[i].1 + a2[i].1 + a3[i].1) * dt);
+                    p.vx += dt / 6.0 * (a1[i].0 + 2.0 * a2[i].0 + 2.0 * a3[i].0 + a4[i].0);
+                    p.vy += dt / 6.0 * (a1[i].1 + 2.0 * a2[i].1 + 2.0 * a3[i].1 + a4[i].1);
                 }
             }
         }
+
+        let (types, boundary, dimentions) = (&self.types, self.boundary, self.dimentions);
+        let len = self.particles.len();
+        self.particles.retain_mut(|p| {
+            let damping = (1.0 - *types.friction(p.p_type as usize).unwrap()).powf(dt);
+            let margin = 2.0 * *types.radius(p.p_type as usize).unwrap();
+            p.vx *= damping;
+            p.vy *= damping;
+            boundary.confine(p, margin, dimentions)
+        });
+        len - self.particles.len()
+    }
+
+    /// Every particle's velocity after being pushed by the forces for `dt`,
+    /// with the particles at the positions in `stage` if given.
+    fn velocities(&mut self, stage: Option<&[Particle]>, dt: f32) -> Vec<(f32, f32)> {
+        let particles = stage.unwrap_or(&self.particles);
+        self.grid.rebuild(
+            particles,
+            self.dimentions.x,
+            self.dimentions.y,
+            self.types.largest_max_r(),
+        );
+        let field = ForceField {
+            particles,
+            types: &self.types,
+            grid: &self.grid,
+            dimentions: self.dimentions,
+            boundary: self.boundary,
+            kernel: self.kernel,
+        };
+        #[cfg(feature = "parallel")]
+        let velocities = (0..particles.len())
+            .into_par_iter()
+            .map_init(Vec::new, |neighbours, i| field.velocity(i, dt, neighbours))
+            .collect();
+        #[cfg(not(feature = "parallel"))]
+        let velocities = {
+            let mut neighbours = Vec::new();
+            (0..particles.len())
+                .map(|i| field.velocity(i, dt, &mut neighbours))
+                .collect()
+        };
+        velocities
+    }
+
+    /// Every particle's acceleration with the particles at the positions in `stage`.
+    fn accelerations(&mut self, stage: &mut [Particle]) -> Vec<(f32, f32)> {
+        for p in stage.iter_mut() {
+            p.vx = 0.0;
+            p.vy = 0.0;
+        }
+        self.velocities(Some(stage), 1.0)
+    }
+
+    /// Move `stage` to where the particles would be after `dt` at their current
+    /// velocity plus `accelerations` applied for `kick`.
+    fn rk4_stage(&self, stage: &mut [Particle], accelerations: &[(f32, f32)], kick: f32, dt: f32) {
+        for (i, (s, p)) in stage.iter_mut().zip(&self.particles).enumerate() {
+            let (ax, ay) = accelerations.get(i).copied().unwrap_or((0.0, 0.0));
+            s.x = p.x + (p.vx + ax * kick) * dt;
+            s.y = p.y + (p.vy + ay * kick) * dt;
+            self.wrap_particle(s);
+        }
+    }
+
+    fn wrap_particle(&self, p: &mut Particle) {
+        let (x, y) = self.wrap_point(p.x, p.y);
+        p.x = x;
+        p.y = y;
     }
 
-    pub fn draw(&self, handle: &mut RaylibDrawHandle, alpha: f32) {
-        for p in self.particles.iter() {
-            handle.draw_circle(
-                (((p.x - self.centre.x) * self.zoom) + self.dimentions.x / 2.0) as i32,
-                (((p.y - self.centre.y) * self.zoom) + self.dimentions.y / 2.0) as i32,
-                RADIUS * self.zoom,
-                self.types.color(p.p_type as usize).unwrap().fade(alpha),
+    /// Draw every particle in its colour from `coloring`.
+    pub fn draw(&self, canvas: &mut impl Canvas, coloring: &Coloring, alpha: f32) {
+        if self.boundary == Boundary::Disc {
+            let (dx, dy) =
+                self.offset_from_centre(0.5 * self.dimentions.x, 0.5 * self.dimentions.y);
+            canvas.draw_circle_lines(
+                (dx * self.zoom) + self.screen.x / 2.0,
+                (dy * self.zoom) + self.screen.y / 2.0,
+                0.5 * self.dimentions.x.min(self.dimentions.y) * self.zoom,
+                Color::WHITE.fade(0.25 * alpha),
             );
         }
+        let half_view = Vector2::new(
+            0.5 * self.screen.x / self.zoom,
+            0.5 * self.screen.y / self.zoom,
+        );
+        for (p, &color) in self.particles.iter().zip(&coloring.colors) {
+            let color = color.fade(alpha);
+            let radius = *self.types.radius(p.p_type as usize).unwrap();
+            let (dx, dy) = self.offset_from_centre(p.x, p.y);
+            for dx in self.images(dx, radius, self.dimentions.x, half_view.x) {
+                for dy in self.images(dy, radius, self.dimentions.y, half_view.y) {
+                    canvas.draw_circle(
+                        (dx * self.zoom) + self.screen.x / 2.0,
+                        (dy * self.zoom) + self.screen.y / 2.0,
+                        radius * self.zoom,
+                        color,
+                    );
+                }
+            }
+        }
+    }
+
+    /// The offset of a point from the view centre, taking the shortest way
+    /// around the world when wrapping.
+    fn offset_from_centre(&self, x: f32, y: f32) -> (f32, f32) {
+        let (dx, dy) = (x - self.centre.x, y - self.centre.y);
+        if self.boundary.wraps() {
+            (
+                min_image(dx, self.dimentions.x),
+                min_image(dy, self.dimentions.y),
+            )
+        } else {
+            (dx, dy)
+        }
+    }
+
+    /// The offsets at which a particle must be drawn along one axis. When
+    /// wrapping this is every copy of it within `half_view` of the view centre,
+    /// including the copy on the opposite side when it straddles the seam and
+    /// the tiled copies seen when zoomed out past the size of the world.
+    fn images(&self, d: f32, radius: f32, size: f32, half_view: f32) -> impl Iterator<Item = f32> {
+        let (first, last) = if self.boundary.wraps() {
+            (
+                ((-half_view - radius - d) / size).ceil() as i32,
+                ((half_view + radius - d) / size).floor() as i32,
+            )
+        } else {
+            (0, 0)
+        };
+        (first..=last).map(move |k| d + k as f32 * size)
     }
 
     pub fn get_index(&self, x: usize, y: usize) -> Option<usize> {
         let c = self.get_centre(x, y);
         for (i, p) in self.particles.iter().enumerate() {
-            let dx = p.x - c.x;
-            let dy = p.y - c.y;
-            if dx * dx + dy * dy < RADIUS * RADIUS {
+            let (mut dx, mut dy) = (p.x - c.x, p.y - c.y);
+            if self.boundary.wraps() {
+                dx = min_image(dx, self.dimentions.x);
+                dy = min_image(dy, self.dimentions.y);
+            }
+            let radius = *self.types.radius(p.p_type as usize).unwrap();
+            if dx * dx + dy * dy < radius * radius {
                 return Some(i);
             }
         }
         None
     }
 
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    /// Add a particle of type `p_type` at rest at `(x, y)`, unless there is no
+    /// such type.
+    pub fn add_particle(&mut self, x: f32, y: f32, p_type: usize) {
+        if p_type >= self.types.len() {
+            return;
+        }
+        let (x, y) = self.wrap_point(x, y);
+        self.particles.push(Particle {
+            x,
+            y,
+            vx: 0.0,
+            vy: 0.0,
+            p_type: p_type as u8,
+        });
+        self.num_particles += 1;
+    }
+
+    /// Remove every particle within `radius` of `(x, y)`, returning how many
+    /// were removed.
+    pub fn remove_particles_within(&mut self, x: f32, y: f32, radius: f32) -> usize {
+        let (wrap, dimentions) = (self.boundary.wraps(), self.dimentions);
+        let before = self.particles.len();
+        self.particles.retain(|p| {
+            let (mut dx, mut dy) = (p.x - x, p.y - y);
+            if wrap {
+                dx = min_image(dx, dimentions.x);
+                dy = min_image(dy, dimentions.y);
+            }
+            dx * dx + dy * dy > radius * radius
+        });
+        let removed = before - self.particles.len();
+        self.num_particles = self.num_particles.saturating_sub(removed);
+        removed
+    }
+
+    pub fn types(&self) -> &ParticleTypes {
+        &self.types
+    }
+
+    pub fn types_mut(&mut self) -> &mut ParticleTypes {
+        &mut self.types
+    }
+
+    pub fn width(&self) -> f32 {
+        self.dimentions.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.dimentions.y
+    }
+
+    /// The width of the view the universe is drawn into, in pixels.
+    pub fn screen_width(&self) -> f32 {
+        self.screen.x
+    }
+
+    pub fn screen_height(&self) -> f32 {
+        self.screen.y
+    }
+
+    /// Where the point `(x, y)` in the world is drawn in the view, without
+    /// wrapping it around.
+    pub fn to_screen(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            (x - self.centre.x) * self.zoom + self.screen.x / 2.0,
+            (y - self.centre.y) * self.zoom + self.screen.y / 2.0,
+        )
+    }
+
+    /// The total kinetic energy of all particles.
+    pub fn kinetic_energy(&self) -> f32 {
+        self.particles
+            .iter()
+            .map(|p| {
+                let mass = *self.types.mass(p.p_type as usize).unwrap();
+                0.5 * mass * (p.vx * p.vx + p.vy * p.vy)
+            })
+            .sum()
+    }
+
     pub fn get_particle_x(&self, index: usize) -> Option<f32> {
         self.particles.get(index).map(|p| p.x)
     }
@@ -243,41 +653,119 @@ impl Universe {
     }
 
     pub fn to_centre(&self, x: usize, y: usize, cam: &mut Camera) {
-        *cam.x_dest_mut() = self.centre.x + (x as f32 - self.dimentions.x / 2.0) / self.zoom;
-        *cam.y_dest_mut() = self.centre.y + (y as f32 - self.dimentions.y / 2.0) / self.zoom;
+        *cam.x_dest_mut() = self.centre.x + (x as f32 - self.screen.x / 2.0) / self.zoom;
+        *cam.y_dest_mut() = self.centre.y + (y as f32 - self.screen.y / 2.0) / self.zoom;
     }
 
     pub fn get_centre(&self, x: usize, y: usize) -> Vector2 {
-        Vector2::new(
-            self.centre.x + (x as f32 - self.dimentions.x / 2.0) / self.zoom,
-            self.centre.y + (y as f32 - self.dimentions.y / 2.0) / self.zoom,
-        )
+        let (x, y) = self.wrap_point(
+            self.centre.x + (x as f32 - self.screen.x / 2.0) / self.zoom,
+            self.centre.y + (y as f32 - self.screen.y / 2.0) / self.zoom,
+        );
+        Vector2::new(x, y)
+    }
+
+    /// Wrap a point back into the world when wrapping is enabled.
+    pub fn wrap_point(&self, x: f32, y: f32) -> (f32, f32) {
+        if self.boundary.wraps() {
+            (
+                x.rem_euclid(self.dimentions.x),
+                y.rem_euclid(self.dimentions.y),
+            )
+        } else {
+            (x, y)
+        }
+    }
+
+    /// The image of `(x, y)` closest to `(from_x, from_y)` when wrapping is enabled.
+    pub fn nearest_image(&self, x: f32, y: f32, from_x: f32, from_y: f32) -> (f32, f32) {
+        if self.boundary.wraps() {
+            (
+                from_x + min_image(x - from_x, self.dimentions.x),
+                from_y + min_image(y - from_y, self.dimentions.y),
+            )
+        } else {
+            (x, y)
+        }
+    }
+
+    /// Call `f(i, j, r)` for every pair of different particles `i` and `j`
+    /// that are `r` apart, where `r` is less than `range`.
+    pub(crate) fn for_each_pair(&self, range: f32, mut f: impl FnMut(usize, usize, f32)) {
+        let mut grid = Grid::new();
+        grid.rebuild(&self.particles, self.dimentions.x, self.dimentions.y, range);
+        let mut neighbours = Vec::new();
+        for (i, p) in self.particles.iter().enumerate() {
+            grid.neighbours(p.x, p.y, self.boundary.wraps(), &mut neighbours);
+            for &j in neighbours.iter() {
+                let q = &self.particles[j];
+                let (mut dx, mut dy) = (q.x - p.x, q.y - p.y);
+                if self.boundary.wraps() {
+                    dx = min_image(dx, self.dimentions.x);
+                    dy = min_image(dy, self.dimentions.y);
+                }
+                let r2 = dx * dx + dy * dy;
+                if i != j && r2 < range * range {
+                    f(i, j, r2.sqrt());
+                }
+            }
+        }
+    }
+
+    /// Let the view move past the edges of the world and zoom out further than
+    /// the whole world, showing tiled copies of it when wrapping.
+    pub fn toggle_unbounded_view(&mut self) {
+        self.unbounded_view = !self.unbounded_view;
+    }
+
+    pub fn unbounded_view(&self) -> bool {
+        self.unbounded_view
+    }
+
+    /// The furthest the view can be zoomed out.
+    pub fn min_zoom(&self) -> f32 {
+        if self.unbounded_view {
+            MIN_UNBOUNDED_ZOOM
+        } else {
+            1.0
+        }
     }
 
     pub fn zoom(&mut self, cx: f32, cy: f32, zoom: f32) {
-        self.centre.x = cx;
-        self.centre.y = cy;
-        self.zoom = 1.0_f32.max(zoom);
-        self.centre.x = min(
-            self.centre.x,
-            self.dimentions.x as f32 * (1.0 - 0.5 / self.zoom),
-        );
-        self.centre.y = min(
-            self.centre.y,
-            self.dimentions.y as f32 * (1.0 - 0.5 / self.zoom),
-        );
-        self.centre.x = max(self.centre.x, self.dimentions.x as f32 * (0.5 / self.zoom));
-        self.centre.y = max(self.centre.y, self.dimentions.y as f32 * (0.5 / self.zoom));
+        self.zoom = zoom.max(self.min_zoom());
+        let (x, y) = self.clamp_view(cx, cy, self.zoom);
+        self.centre.x = x;
+        self.centre.y = y;
+    }
+
+    /// Where the view would be centred if asked to centre on `(cx, cy)` at
+    /// `zoom`. The view is kept inside the world unless it wraps around or
+    /// the view is unbounded.
+    pub fn clamp_view(&self, cx: f32, cy: f32, zoom: f32) -> (f32, f32) {
+        if self.boundary.wraps() || self.unbounded_view {
+            return (cx, cy);
+        }
+        let zoom = zoom.max(self.min_zoom());
+        (
+            clamp_centre(cx, self.dimentions.x, self.screen.x / zoom),
+            clamp_centre(cy, self.dimentions.y, self.screen.y / zoom),
+        )
     }
 }
 
 impl Display for Universe {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut friction = String::new();
+        let mut mass = String::new();
+        let mut radius = String::new();
         let mut attract = String::new();
         let mut min_r = String::new();
         let mut max_r = String::new();
         let range = 0..self.types.len();
         for i in range.clone() {
+            friction.push_str(format!("{:.4}    ", self.types.friction(i).unwrap()).as_str());
+            mass.push_str(format!("{:.4}    ", self.types.mass(i).unwrap()).as_str());
+            radius.push_str(format!("{:.4}    ", self.types.radius(i).unwrap()).as_str());
             for j in range.clone() {
                 attract.push_str(format!("{:.4}    ", self.types.attract(i, j).unwrap()).as_str());
                 min_r.push_str(format!("{:.4}    ", self.types.min_r(i, j).unwrap()).as_str());
@@ -286,12 +774,89 @@ impl Display for Universe {
         }
         write!(
             f,
-            "\nAttract:\n{}\nMinR\n{}\nMaxR\n{}\n",
-            attract, min_r, max_r
+            "\nSeed: {}\nBoundary: {}\nKernel: {}\nIntegrator: {} (dt {})\nFriction:\n{}\nMass:\n{}\nRadius:\n{}\nAttract:\n{}\nMinR\n{}\nMaxR\n{}\n",
+            self.seed,
+            self.boundary.name(),
+            self.kernel.name(),
+            self.integrator.name(),
+            self.dt,
+            friction,
+            mass, radius, attract, min_r, max_r
         )
     }
 }
 
+/// A read-only view of everything needed to compute the forces on a particle.
+struct ForceField<'a> {
+    particles: &'a [Particle],
+    types: &'a ParticleTypes,
+    grid: &'a Grid,
+    dimentions: Vector2,
+    boundary: Boundary,
+    kernel: &'a dyn ForceKernel,
+}
+
+impl ForceField<'_> {
+    /// The velocity of particle `i` after the forces from its neighbours are
+    /// applied for `dt`.
+    fn velocity(&self, i: usize, dt: f32, neighbours: &mut Vec<usize>) -> (f32, f32) {
+        let p = *self.particles.get(i).unwrap();
+        let inv_mass = dt / *self.types.mass(p.p_type as usize).unwrap();
+        let (mut vx, mut vy) = (p.vx, p.vy);
+        self.grid
+            .neighbours(p.x, p.y, self.boundary.wraps(), neighbours);
+        for &j in neighbours.iter() {
+            let q = *self.particles.get(j).unwrap();
+
+            let (mut dx, mut dy) = (q.x - p.x, q.y - p.y);
+            if self.boundary.wraps() {
+                dx = min_image(dx, self.dimentions.x);
+                dy = min_image(dy, self.dimentions.y);
+            }
+            let r2 = dx * dx + dy * dy;
+
+            let min_r = self
+                .types
+                .min_r(p.p_type as usize, q.p_type as usize)
+                .unwrap()
+                .max(
+                    self.types
+                        .contact_distance(p.p_type as usize, q.p_type as usize),
+                );
+            let max_r = *self
+                .types
+                .max_r(p.p_type as usize, q.p_type as usize)
+                .unwrap();
+            if r2 > max_r * max_r || r2 < 0.01 {
+                continue;
+            }
+            let r = r2.sqrt();
+            dx /= r;
+            dy /= r;
+            let rule = PairRule {
+                attract: *self
+                    .types
+                    .attract(p.p_type as usize, q.p_type as usize)
+                    .unwrap(),
+                min_r,
+                max_r,
+            };
+            let f = self
+                .kernel
+                .force(p.p_type as usize, q.p_type as usize, r, &rule);
+
+            vx += f * dx * inv_mass;
+            vy += f * dy * inv_mass;
+        }
+        if let Some((fx, fy)) = self.boundary.wall_force(p.x, p.y, self.dimentions) {
+            vx += fx * inv_mass;
+            vy += fy * inv_mass;
+        }
+        (vx, vy)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct RandomSettings {
     attract_mean: f32,
     attract_std: f32,
@@ -299,6 +864,12 @@ pub struct RandomSettings {
     min_r_upper: f32,
     max_r_lower: f32,
     max_r_upper: f32,
+    friction_lower: f32,
+    friction_upper: f32,
+    mass_lower: f32,
+    mass_upper: f32,
+    radius_lower: f32,
+    radius_upper: f32,
 }
 
 impl RandomSettings {
@@ -310,22 +881,120 @@ impl RandomSettings {
             min_r_upper: 0.0,
             max_r_lower: 0.0,
             max_r_upper: 0.0,
+            friction_lower: 0.0,
+            friction_upper: 0.0,
+            mass_lower: 1.0,
+            mass_upper: 1.0,
+            radius_lower: DEFAULT_RADIUS,
+            radius_upper: DEFAULT_RADIUS,
+        }
+    }
+    pub fn from_preset(preset: &Preset) -> Self {
+        RandomSettings {
+            attract_mean: preset.attract_mean,
+            attract_std: preset.attract_std,
+            min_r_lower: preset.min_r.0,
+            min_r_upper: preset.min_r.1,
+            max_r_lower: preset.max_r.0,
+            max_r_upper: preset.max_r.1,
+            friction_lower: preset.friction.0,
+            friction_upper: preset.friction.1,
+            mass_lower: preset.mass.0,
+            mass_upper: preset.mass.1,
+            radius_lower: preset.radius.0,
+            radius_upper: preset.radius.1,
+        }
+    }
+}
+
+impl Default for RandomSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{boundary::BOUNDARIES, kernel::KERNELS, preset::DIVERSITY};
+
+    /// Every particle's velocity after `dt`, from a plain pass over every
+    /// other particle in index order.
+    fn expected_velocities(universe: &Universe, dt: f32) -> Vec<(f32, f32)> {
+        let (types, size) = (universe.types(), universe.dimentions);
+        let wraps = universe.boundary().wraps();
+        let particles = universe.particles();
+        let mut velocities = Vec::new();
+        for p in particles {
+            let pt = p.p_type as usize;
+            let inv_mass = dt / *types.mass(pt).unwrap();
+            let (mut vx, mut vy) = (p.vx, p.vy);
+            for q in particles {
+                let qt = q.p_type as usize;
+                let (mut dx, mut dy) = (q.x - p.x, q.y - p.y);
+                if wraps {
+                    dx = min_image(dx, size.x);
+                    dy = min_image(dy, size.y);
+                }
+                let r2 = dx * dx + dy * dy;
+                let max_r = *types.max_r(pt, qt).unwrap();
+                if r2 > max_r * max_r || r2 < 0.01 {
+                    continue;
+                }
+                let r = r2.sqrt();
+                let rule = PairRule {
+                    attract: *types.attract(pt, qt).unwrap(),
+                    min_r: types
+                        .min_r(pt, qt)
+                        .unwrap()
+                        .max(types.contact_distance(pt, qt)),
+                    max_r,
+                };
+                let f = universe.kernel().force(pt, qt, r, &rule);
+                vx += f * (dx / r) * inv_mass;
+                vy += f * (dy / r) * inv_mass;
+            }
+            if let Some((fx, fy)) = universe.boundary().wall_force(p.x, p.y, size) {
+                vx += fx * inv_mass;
+                vy += fy * inv_mass;
+            }
+            velocities.push((vx, vy));
         }
+        velocities
+    }
+
+    fn bits(velocities: &[(f32, f32)]) -> Vec<(u32, u32)> {
+        velocities
+            .iter()
+            .map(|&(vx, vy)| (vx.to_bits(), vy.to_bits()))
+            .collect()
     }
-    pub fn re_seed(
-        &mut self,
-        attract_mean: f32,
-        attract_std: f32,
-        min_r: (f32, f32),
-        max_r: (f32, f32),
-    ) {
-        *self = RandomSettings {
-            attract_mean,
-            attract_std,
-            min_r_lower: min_r.0,
-            min_r_upper: min_r.1,
-            max_r_lower: max_r.0,
-            max_r_upper: max_r.1,
+
+    /// The neighbour grid, spread over threads with the `parallel` feature,
+    /// gives exactly the same velocities as a serial pass over every pair.
+    #[test]
+    fn velocities_match_a_pass_over_every_pair() {
+        for &boundary in BOUNDARIES.iter() {
+            for &kernel in KERNELS.iter() {
+                let mut universe = Universe::new(0, 0, 600.0, 400.0, 0);
+                universe.set_population(6, 200);
+                universe.re_seed(&DIVERSITY, 7);
+                universe.set_boundary(boundary);
+                universe.set_kernel(kernel);
+                for step in 0..10 {
+                    let dt = universe.dt();
+                    let expected = expected_velocities(&universe, dt);
+                    let velocities = universe.velocities(None, dt);
+                    assert!(
+                        bits(&velocities) == bits(&expected),
+                        "{} with {} differs at step {}",
+                        boundary.name(),
+                        kernel.name(),
+                        step
+                    );
+                    universe.step();
+                }
+            }
         }
     }
 }